@@ -1,13 +1,34 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use calamine::{DataType, open_workbook, Reader, Xlsx};
+use calamine::DataType;
 use clap::ArgMatches;
+use encoding_rs::Encoding;
 use xlsxwriter::{Workbook};
-use crate::command::cve::{utils};
+use crate::command::cve::{utils, CVE_API};
+use crate::command::cve::columns::{ColumnField, ColumnMatcher, ColumnProfile};
+use crate::command::cve::format::{self, Finding};
 use crate::command::lib::image;
+use crate::command::lib::table;
 use crate::command::lib::image::ImageIndex;
 
+/// The columns `parse_object` reads from the component sheet.
+const COMPONENT_SHEET_FIELDS: [ColumnField; 5] = [
+    ColumnField::Component,
+    ColumnField::Version,
+    ColumnField::Object,
+    ColumnField::VulCount,
+    ColumnField::BinaryObject,
+];
+
+/// The columns `parse_component_cves` reads from the vulnerability sheet.
+const VULNERABILITY_SHEET_FIELDS: [ColumnField; 4] = [
+    ColumnField::Component,
+    ColumnField::Version,
+    ColumnField::Cve,
+    ColumnField::Object,
+];
+
 pub fn handler(matches: &ArgMatches) {
     let path = matches.get_one::<String>("path").unwrap();
     let files: Vec<&String> = matches
@@ -19,6 +40,18 @@ pub fn handler(matches: &ArgMatches) {
     let detail = matches.get_flag("detail");
     let release = matches.get_flag("release");
     let output = matches.get_one::<String>("output").unwrap();
+    let input_format = matches.get_one::<String>("format").unwrap();
+    let vex_annotate = matches.get_flag("vex_annotate");
+    let delimiter = matches.get_one::<u8>("delimiter").copied();
+    let encoding = matches.get_one::<&'static Encoding>("encoding").copied();
+    let columns = match matches.get_one::<String>("columns") {
+        Some(path) => match ColumnProfile::load(path) {
+            Ok(profile) => profile.compile().unwrap_or_else(|e| panic!("{}", e)),
+            Err(e) => panic!("read columns {} error: {}", path, e),
+        },
+        None => ColumnMatcher::default(),
+    };
+    super::register_providers(matches);
 
     if !Path::exists(Path::new(path)) {
         fs::create_dir(path).unwrap();
@@ -36,6 +69,8 @@ pub fn handler(matches: &ArgMatches) {
     let mut object_map: HashMap<String, HashMap<String, Vec<CveComponent>>> = HashMap::new();
     let mut component_map: HashMap<String, Vec<Cve>> = HashMap::new();
     let mut cve_map: HashMap<String, String> = HashMap::new();
+    let mut purl_map: HashMap<String, String> = HashMap::new();
+    let mut cpe_map: HashMap<String, String> = HashMap::new();
     let mut image_index: ImageIndex = image::ImageIndex::new(HashMap::new());
 
     if release {
@@ -43,26 +78,76 @@ pub fn handler(matches: &ArgMatches) {
     }
 
     for file in files.iter() {
-        let mut workbook: Xlsx<_> = open_workbook(file).unwrap();
+        if input_format != format::FORMAT_BDBA {
+            match format::load(input_format, file, encoding) {
+                Ok(findings) => {
+                    // SBOM components carry no cves, ask the local providers for them
+                    let mut findings = format::resolve_packages(findings, |x| {
+                        CVE_API.lock().unwrap().query_package(&x.purl, &x.version)
+                    });
+                    let total = findings.len();
+                    findings.retain(|x| vex_annotate || !x.is_not_affected());
+                    if findings.len() < total {
+                        println!("{}: {} not affected findings excluded by vex", file, total - findings.len());
+                    }
+                    parse_findings(
+                        &findings,
+                        &mut object_map,
+                        &mut component_map,
+                        &mut cve_map,
+                        &mut purl_map,
+                        &mut cpe_map,
+                    )
+                }
+                Err(e) => panic!("read {} error: {}", file, e),
+            }
+            continue;
+        }
+        if format::bdba::is_export(file) {
+            let tables = format::bdba::load(file, delimiter, encoding).unwrap_or_else(|e| panic!("read {} error: {}", file, e));
+            // every table is matched by its headers, so each parser takes the tables it knows
+            for table in tables.iter() {
+                parse_component_cves(table, &mut component_map, &mut cve_map, &columns);
+                parse_object(table, &mut object_map, release, &image_index, &columns);
+            }
+            continue;
+        }
+        let sheets = table::open(file, delimiter, encoding).unwrap_or_else(|e| panic!("read {} error: {}", file, e));
 
         // parse component's cve
-        let component_sheet = workbook.worksheet_range(sheet_ext).unwrap();
-        parse_component_cves(&component_sheet, &mut component_map, &mut cve_map);
+        match select_sheet(&sheets, sheet_ext, &VULNERABILITY_SHEET_FIELDS, &columns) {
+            Ok(i) => {
+                println!("{}: vulnerability sheet {}", file, sheets[i].0);
+                parse_component_cves(&sheets[i].1, &mut component_map, &mut cve_map, &columns);
+            }
+            Err(e) => println!("{}: no vulnerability sheet found, {}", file, e),
+        }
 
         // parse object's component
-        let object_sheet = workbook.worksheet_range(sheet).unwrap();
-        parse_object(&object_sheet, &mut object_map, release, &image_index);
+        match select_sheet(&sheets, sheet, &COMPONENT_SHEET_FIELDS, &columns) {
+            Ok(i) => {
+                println!("{}: component sheet {}", file, sheets[i].0);
+                parse_object(&sheets[i].1, &mut object_map, release, &image_index, &columns);
+            }
+            Err(e) => println!("{}: no component sheet found, {}", file, e),
+        }
     }
 
-    write_component_output(&component_map, &mut out);
+    write_component_output(&component_map, &purl_map, &cpe_map, &mut out);
     write_object_output(&object_map, &component_map, &mut out);
     utils::write_cve_output(&cve_map, &mut out, detail);
     println!(
-        "object num: {:?}\ncomponent num: {:?}\ncve num: {:?}",
+        "object num: {:?}\ncomponent num: {:?}\ncve num: {:?}\nkev num: {:?}",
         object_map.len(),
         component_map.len(),
-        cve_map.len()
+        cve_map.len(),
+        cve_map.keys().filter(|x| utils::is_kev(x)).count()
     );
+    if detail {
+        if let Some(stats) = CVE_API.lock().unwrap().cache_stats() {
+            println!("{}", stats);
+        }
+    }
     println!("inuput: {:#?}\noutput: {:#?}", files, output);
 }
 
@@ -104,11 +189,13 @@ impl Clone for CveComponent {
 struct Cve {
     cve: String,
     binary: String,
+    /// The VEX analysis state of imported findings.
+    vex: String,
 }
 
 impl Cve {
     fn new(cve: String, binary: String) -> Cve {
-        Cve { cve, binary }
+        Cve { cve, binary, vex: String::new() }
     }
 }
 
@@ -130,6 +217,7 @@ fn write_object_output(
     if !object_map.is_empty() {
         let format1 = utils::set_title_format();
         let format2 = utils::set_content_format();
+        let format3 = utils::set_kev_format();
         let mut sheet1 = out.add_worksheet(Some("image")).unwrap();
         sheet1.write_string(0, 0, "image", Some(&format1)).unwrap();
 
@@ -147,6 +235,7 @@ fn write_object_output(
         sheet1.write_string(0, 5, "object", Some(&format1)).unwrap();
 
         sheet1.write_string(0, 6, "path", Some(&format1)).unwrap();
+        sheet1.write_string(0, 7, "vex", Some(&format1)).unwrap();
 
         let mut object_keys: Vec<String> = object_map.keys().map(|x| x.to_string()).collect();
         object_keys.sort();
@@ -187,7 +276,7 @@ fn write_object_output(
                                             global_index as u32,
                                             4,
                                             cve_detail.cve.as_str(),
-                                            Some(&format2),
+                                            Some(if utils::is_kev(&cve_detail.cve) { &format3 } else { &format2 }),
                                         )
                                         .unwrap();
                                     sheet1
@@ -198,6 +287,11 @@ fn write_object_output(
                                             Some(&format2),
                                         )
                                         .unwrap();
+                                    if !cve_detail.vex.is_empty() {
+                                        sheet1
+                                            .write_string(global_index as u32, 7, &cve_detail.vex, Some(&format2))
+                                            .unwrap();
+                                    }
                                     global_index += 1;
                                     image_merge_end += 1;
                                     comp_merge_end += 1;
@@ -275,6 +369,7 @@ fn parse_object(
     object_map: &mut HashMap<String, HashMap<String, Vec<CveComponent>>>,
     release: bool,
     image_index: &ImageIndex,
+    columns: &ColumnMatcher,
 ) {
     let mut component_index: usize = 0;
     let mut version_index: usize = 0;
@@ -287,53 +382,29 @@ fn parse_object(
 
     for (index, vals) in sheet.rows().enumerate() {
         if index == 0 {
-            for (header_idx, header_content) in vals.iter().enumerate() {
-                if let calamine::DataType::String(val) = header_content {
-                    if utils::is_column_field_component(&val) {
-                        component_index = header_idx;
-                    } else if utils::is_column_field_version(&val) {
-                        version_index = header_idx;
-                    } else if utils::is_column_field_object(&val) {
-                        object_index = header_idx;
-                    } else if utils::is_column_field_vul_count(&val) {
-                        vulnerability_index = header_idx;
-                    } else if utils::is_column_field_binary_object(&val) {
-                        binary_object_index = header_idx;
-                    }
+            match columns.locate_all(&header_row(vals), &COMPONENT_SHEET_FIELDS) {
+                Some(v) => {
+                    component_index = v[0];
+                    version_index = v[1];
+                    object_index = v[2];
+                    vulnerability_index = v[3];
+                    binary_object_index = v[4];
                 }
-            }
-            // println!(
-            //     "object_index:{:#?} component_index:{:#?} version_index:{:#?} vulnerability_index:{:#?} binary_object_index:{:#?}",
-            //     object_index, component_index, version_index, vulnerability_index, binary_object_index
-            // );
-            if component_index == version_index
-                || component_index == object_index
-                || component_index == vulnerability_index
-                || component_index == binary_object_index
-                || version_index == object_index
-                || version_index == vulnerability_index
-                || version_index == binary_object_index
-                || object_index == vulnerability_index
-                || object_index == binary_object_index
-                || vulnerability_index == binary_object_index
-            {
-                break;
+                None => break,
             }
         } else {
-            let vulnerability = match vals.get(vulnerability_index) {
+            // re-saved workbooks keep the count as a number, CSV exports may leave it blank
+            let vulnerability: usize = match vals.get(vulnerability_index) {
                 Some(v) => match v {
-                    DataType::String(v) => v,
-                    DataType::Int(_)
-                    | DataType::Float(_)
-                    | DataType::Bool(_)
-                    | DataType::Error(_)
-                    | DataType::Empty => "0",
-                    _ => "0",
+                    DataType::String(v) => v.trim().parse().unwrap_or(0),
+                    DataType::Int(v) => (*v).max(0) as usize,
+                    DataType::Float(v) => v.max(0.0) as usize,
+                    DataType::Bool(_) | DataType::Error(_) | DataType::Empty => 0,
+                    _ => 0,
                 },
-                None => "0",
+                None => 0,
             };
 
-            let vulnerability = vulnerability.parse::<usize>().unwrap();
             if vulnerability == 0 {
                 continue;
             }
@@ -468,6 +539,112 @@ fn parse_object(
     }
 }
 
+/// Adds the findings of an importer the way `parse_component_cves` and `parse_object` add BDBA rows,
+/// a finding without a cve only lists its component.
+fn parse_findings(
+    findings: &[Finding],
+    object_map: &mut HashMap<String, HashMap<String, Vec<CveComponent>>>,
+    component_map: &mut HashMap<String, Vec<Cve>>,
+    cve_map: &mut HashMap<String, String>,
+    purl_map: &mut HashMap<String, String>,
+    cpe_map: &mut HashMap<String, String>,
+) {
+    // the distinct cves of every (object, component, path), in report order
+    let mut binaries: Vec<((String, String, String), Vec<String>)> = Vec::new();
+    for finding in findings.iter() {
+        if finding.component.is_empty() || finding.version.is_empty() {
+            continue;
+        }
+        let component = finding.component_id();
+        if !finding.purl.is_empty() && !purl_map.contains_key(&component) {
+            purl_map.insert(component.clone(), finding.purl.clone());
+        }
+        if !finding.cpe.is_empty() && !cpe_map.contains_key(&component) {
+            cpe_map.insert(component.clone(), finding.cpe.clone());
+        }
+        if finding.cve.is_empty() {
+            component_map.entry(component).or_default();
+            continue;
+        }
+        let mut cve_inner = Cve::new(finding.cve.clone(), finding.object_path());
+        cve_inner.vex = finding.vex.clone();
+        if let Some(cves) = component_map.get_mut(&component) {
+            if !cves.contains(&cve_inner) {
+                cves.push(cve_inner.clone());
+            }
+        } else {
+            component_map.insert(component.clone(), vec![cve_inner.clone()]);
+        }
+        if !cve_map.contains_key(&cve_inner.cve) {
+            cve_map.insert(cve_inner.cve.clone(), format::advisory_link(&cve_inner.cve));
+        }
+
+        let key = (finding.object.clone(), component, finding.path.clone());
+        match binaries.iter_mut().find(|x| x.0 == key) {
+            Some((_, cves)) => {
+                if !cves.contains(&finding.cve) {
+                    cves.push(finding.cve.clone());
+                }
+            }
+            None => binaries.push((key, vec![finding.cve.clone()])),
+        }
+    }
+    for ((object, component, path), cves) in binaries {
+        update_object_cve_component(object_map, &object, CveComponent::new(component, path, cves.len()));
+    }
+}
+
+/// The sheet having every column of `fields`: the sheet called `name` if it does, else the first
+/// sheet with the most matching headers. The error names the closest sheet when none has them all.
+fn select_sheet(
+    sheets: &[(String, calamine::Range<DataType>)],
+    name: &str,
+    fields: &[ColumnField],
+    columns: &ColumnMatcher,
+) -> Result<usize, String> {
+    let mut scores: Vec<(usize, bool)> = Vec::new();
+    for (_, range) in sheets.iter() {
+        let headers = range.rows().next().map(header_row).unwrap_or_default();
+        scores.push((
+            columns.score(&headers, fields),
+            columns.locate_all(&headers, fields).is_some(),
+        ));
+    }
+    if let Some(i) = sheets.iter().position(|x| x.0 == name) {
+        if scores[i].1 {
+            return Ok(i);
+        }
+    }
+    let mut best: Option<usize> = None;
+    for (i, score) in scores.iter().enumerate() {
+        if score.1 && best.map(|x| score.0 > scores[x].0).unwrap_or(true) {
+            best = Some(i);
+        }
+    }
+    if let Some(i) = best {
+        return Ok(i);
+    }
+    match (0..sheets.len()).rev().max_by_key(|&i| scores[i].0) {
+        Some(i) if scores[i].0 > 0 => Err(format!(
+            "closest sheet {} has {}/{} columns",
+            sheets[i].0,
+            scores[i].0,
+            fields.len()
+        )),
+        _ => Err(String::from("no sheet has any of the columns")),
+    }
+}
+
+/// The string cells of a header row, other cells as empty headers.
+fn header_row(vals: &[DataType]) -> Vec<&str> {
+    vals.iter()
+        .map(|x| match x {
+            DataType::String(v) => v.as_str(),
+            _ => "",
+        })
+        .collect()
+}
+
 fn update_object_cve_component(object_map: &mut HashMap<String, HashMap<String, Vec<CveComponent>>>,
                                object_key: &str,
                                cve_component: CveComponent) {
@@ -484,31 +661,55 @@ fn update_object_cve_component(object_map: &mut HashMap<String, HashMap<String,
     }
 }
 
-fn write_component_output(component_map: &HashMap<String, Vec<Cve>>, out: &mut Workbook) {
+fn write_component_output(
+    component_map: &HashMap<String, Vec<Cve>>,
+    purl_map: &HashMap<String, String>,
+    cpe_map: &HashMap<String, String>,
+    out: &mut Workbook,
+) {
     if !component_map.is_empty() {
         let format1 = utils::set_title_format();
         let format2 = utils::set_content_format();
+        let format3 = utils::set_kev_format();
         let mut sheet1 = out.add_worksheet(Some("component")).unwrap();
         sheet1
             .write_string(0, 0, "component", Some(&format1))
             .unwrap();
         sheet1.write_string(0, 1, "cve", Some(&format1)).unwrap();
         sheet1.write_string(0, 2, "num", Some(&format1)).unwrap();
+        sheet1.write_string(0, 3, "kev", Some(&format1)).unwrap();
+        sheet1.write_string(0, 4, "purl", Some(&format1)).unwrap();
+        sheet1.write_string(0, 5, "cpe", Some(&format1)).unwrap();
 
         let mut component_keys: Vec<String> = component_map.keys().map(|x| x.to_string()).collect();
         component_keys.sort();
 
         for (index, k) in component_keys.iter().enumerate() {
             if let Some(v) = component_map.get(k) {
+                let kev: Vec<String> = v.iter().filter(|a| utils::is_kev(&a.cve)).map(|a| a.clone().cve).collect();
+                let format = if kev.is_empty() { &format2 } else { &format3 };
                 sheet1
-                    .write_string((index + 1) as u32, 0, k, Some(&format2))
+                    .write_string((index + 1) as u32, 0, k, Some(format))
                     .unwrap();
                 sheet1
-                    .write_string((index + 1) as u32, 1, v.iter().map(|a| a.clone().cve).collect::<Vec<String>>().join("\n").as_str(), Some(&format2))
+                    .write_string((index + 1) as u32, 1, v.iter().map(|a| a.clone().cve).collect::<Vec<String>>().join("\n").as_str(), Some(format))
                     .unwrap();
                 sheet1
-                    .write_number((index + 1) as u32, 2, v.len() as f64, Some(&format2))
+                    .write_number((index + 1) as u32, 2, v.len() as f64, Some(format))
                     .unwrap();
+                sheet1
+                    .write_string((index + 1) as u32, 3, kev.join("\n").as_str(), Some(format))
+                    .unwrap();
+                if let Some(purl) = purl_map.get(k) {
+                    sheet1
+                        .write_string((index + 1) as u32, 4, purl, Some(format))
+                        .unwrap();
+                }
+                if let Some(cpe) = cpe_map.get(k) {
+                    sheet1
+                        .write_string((index + 1) as u32, 5, cpe, Some(format))
+                        .unwrap();
+                }
             }
         }
     }
@@ -518,6 +719,7 @@ fn parse_component_cves(
     sheet: &calamine::Range<DataType>,
     component_map: &mut HashMap<String, Vec<Cve>>,
     cve_map: &mut HashMap<String, String>,
+    columns: &ColumnMatcher,
 ) {
     let mut component_index: usize = 0;
     let mut version_index: usize = 0;
@@ -526,31 +728,14 @@ fn parse_component_cves(
 
     for (index, vals) in sheet.rows().enumerate() {
         if index == 0 {
-            for (header_idx, header_content) in vals.iter().enumerate() {
-                if let calamine::DataType::String(val) = header_content {
-                    if utils::is_column_field_component(&val) {
-                        component_index = header_idx;
-                    } else if utils::is_column_field_version(&val) {
-                        version_index = header_idx;
-                    } else if utils::is_column_field_cve(&val) {
-                        cve_index = header_idx;
-                    } else if utils::is_column_field_object(&val) {
-                        object_index = header_idx;
-                    }
+            match columns.locate_all(&header_row(vals), &VULNERABILITY_SHEET_FIELDS) {
+                Some(v) => {
+                    component_index = v[0];
+                    version_index = v[1];
+                    cve_index = v[2];
+                    object_index = v[3];
                 }
-            }
-            // println!(
-            //     "component_index:{:#?} version_index:{:#?} cve_index:{:#?}",
-            //     component_index, version_index, cve_index
-            // );
-            if component_index == version_index
-                || component_index == cve_index
-                || component_index == object_index
-                || version_index == cve_index
-                || version_index == object_index
-                || cve_index == object_index
-            {
-                break;
+                None => break,
             }
         } else {
             let component = match vals.get(component_index) {
@@ -644,3 +829,113 @@ fn parse_component_cves(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn sheet(name: &str, headers: &[&str]) -> (String, calamine::Range<DataType>) {
+        let mut range = calamine::Range::new((0, 0), (0, headers.len() as u32 - 1));
+        for (i, header) in headers.iter().enumerate() {
+            range.set_value((0, i as u32), DataType::String(header.to_string()));
+        }
+        (name.to_string(), range)
+    }
+
+    #[test]
+    fn test_select_sheet() {
+        let columns = ColumnMatcher::default();
+        let sheets = vec![
+            sheet("Summary", &["Product", "Status"]),
+            sheet("Vulnerabilities", &["Component", "Version", "CVE", "Object full path"]),
+            sheet("Components", &["Component", "Version", "Object", "Object full path", "Vulnerability count"]),
+        ];
+        assert_eq!(select_sheet(&sheets, "组件报告", &COMPONENT_SHEET_FIELDS, &columns), Ok(2));
+        assert_eq!(select_sheet(&sheets, "漏洞报告", &VULNERABILITY_SHEET_FIELDS, &columns), Ok(1));
+        // a named sheet without the columns does not win
+        assert_eq!(select_sheet(&sheets, "Summary", &VULNERABILITY_SHEET_FIELDS, &columns), Ok(1));
+        assert_eq!(
+            select_sheet(&sheets[..2], "组件报告", &COMPONENT_SHEET_FIELDS, &columns),
+            Err(String::from("closest sheet Vulnerabilities has 3/5 columns"))
+        );
+        assert_eq!(
+            select_sheet(&sheets[..1], "组件报告", &COMPONENT_SHEET_FIELDS, &columns),
+            Err(String::from("no sheet has any of the columns"))
+        );
+    }
+
+    #[test]
+    fn test_parse_object_counts() {
+        let data = "Component,Version,Object,Object full path,Vulnerability count
+openssl,1.0.2k,libssl.so,app,2
+zlib,1.2.11,libz.so,app,
+curl,7.29.0,libcurl.so,app, 1
+expat,2.1.0,libexpat.so,app,x
+";
+        let mut sheet = table::parser_delimited(data, None).unwrap();
+        // numeric cells of a re-saved workbook
+        sheet.set_value((5, 0), DataType::String(String::from("bzip2")));
+        sheet.set_value((5, 1), DataType::String(String::from("1.0.6")));
+        sheet.set_value((5, 3), DataType::String(String::from("app")));
+        sheet.set_value((5, 4), DataType::Float(3.0));
+        sheet.set_value((6, 0), DataType::String(String::from("xz")));
+        sheet.set_value((6, 1), DataType::String(String::from("5.2.2")));
+        sheet.set_value((6, 3), DataType::String(String::from("app")));
+        sheet.set_value((6, 4), DataType::Int(1));
+
+        let mut object_map = HashMap::new();
+        let image_index = ImageIndex::new(HashMap::new());
+        parse_object(&sheet, &mut object_map, false, &image_index, &ColumnMatcher::default());
+        let mut counts: Vec<(String, usize)> = object_map["app"]
+            .values()
+            .flatten()
+            .map(|x| (x.component.clone(), x.cve))
+            .collect();
+        counts.sort();
+        assert_eq!(
+            counts,
+            vec![
+                (String::from("bzip21.0.6"), 3),
+                (String::from("curl7.29.0"), 1),
+                (String::from("openssl1.0.2k"), 2),
+                (String::from("xz5.2.2"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_bdba_bundle() {
+        let dir = std::env::temp_dir().join("etool-analyze-bundle-test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("components.csv"),
+            "Component,Version,Object,Object full path,Vulnerability count\nopenssl,1.0.2k,libssl.so,app,1\nzlib,1.2.11,libz.so,app,\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("vulnerabilities.csv"),
+            "Component,Version,CVE,Object full path\nopenssl,1.0.2k,CVE-2016-2105,scan/app.tar/layer/app/1/2/usr/lib64/libssl.so\n",
+        )
+        .unwrap();
+
+        let mut object_map = HashMap::new();
+        let mut component_map = HashMap::new();
+        let mut cve_map = HashMap::new();
+        let image_index = ImageIndex::new(HashMap::new());
+        let columns = ColumnMatcher::default();
+        // each table feeds the parser whose columns it has, as `handler` runs both on every table
+        for table in format::bdba::load(dir.to_str().unwrap(), None, None).unwrap().iter() {
+            parse_component_cves(table, &mut component_map, &mut cve_map, &columns);
+            parse_object(table, &mut object_map, false, &image_index, &columns);
+        }
+        let cves: &Vec<Cve> = &component_map["openssl1.0.2k"];
+        assert_eq!(cves.len(), 1);
+        assert_eq!(cves[0].cve, "CVE-2016-2105");
+        assert_eq!(cves[0].binary, "app/usr/lib64/libssl.so");
+        assert_eq!(cve_map.len(), 1);
+        // the blank count of zlib is no panic but no vulnerability
+        assert_eq!(object_map["app"].len(), 1);
+        assert!(object_map["app"].contains_key("openssl1.0.2k"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,14 +1,16 @@
 use crate::command::cve::api::base::{AsyncHttpClient, HttpClient};
-use crate::command::cve::api::lib::{Cve, CveApi};
+use crate::command::cve::api::cvss::parse_score;
+use crate::command::cve::api::lib::CveApi;
+use crate::command::cve::api::record::{parse_date, CveRecord, CvssMetric, Severity};
 use select::document::Document;
 use select::node::Node;
-use select::predicate::{Class, Name, Predicate};
+use select::predicate::{Class, Name};
 use serde::{self, Deserialize, Serialize};
 use std::fs;
 use std::future::Future;
 use std::io::Write;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 pub const ALI_YUN_CVE_API: &str = "AliyunApi";
 
@@ -23,48 +25,6 @@ impl AliyunApi {
         }
     }
 
-    fn trim_node(&self, s: String) -> String {
-        s.trim().trim_matches('\n').to_string()
-    }
-
-    fn parser_html(&self, target: &str) -> AliyunCve {
-        let document = Document::from(target);
-        let mut cve = AliyunCve::new();
-        for node in document.find(Class("header__title__text")).take(1) {
-            cve.title = self.trim_node(node.text());
-        }
-        for node in document.find(Class("metric-value")).take(1) {
-            cve.id = self.trim_node(node.text());
-        }
-
-        for node in document.find(Class("metric-value")).take(3) {
-            cve.fix_label = self.trim_node(node.text());
-        }
-        for node in document.find(Class("metric-value")).take(4) {
-            cve.publish = self.trim_node(node.text());
-        }
-        for node in document
-            .find(Class("text-detail").child(Name("div")))
-            .collect::<Vec<Node>>()
-        {
-            cve.description += &format!("{}\n", self.trim_node(node.text()));
-        }
-        for node in document.find(Class("text-detail").and(Name("div"))).take(2) {
-            cve.suggestion = self.trim_node(node.text());
-        }
-        for node in document
-            .find(Class("cvss-breakdown").descendant(Class("cvss-breakdown__score")))
-            .take(2)
-        {
-            cve.score = self.trim_node(node.text());
-        }
-        for node in document.find(Class("cvss-breakdown__desc")).take(1) {
-            cve.effect = self.trim_node(node.text());
-        }
-        // println!("{:#?}", cve);
-        cve
-    }
-
     #[allow(unused)]
     fn write_file(&self, path: &str, response: reqwest::blocking::Response) {
         let mut file = fs::OpenOptions::new()
@@ -83,24 +43,29 @@ impl Default for AliyunApi {
 }
 
 impl CveApi for AliyunApi {
-    fn query(&self, id: &str) -> Box<dyn Cve> {
+    fn query(&self, id: &str) -> CveRecord {
         let mut cve = AliyunCve::new();
         let resp = self
             .http_client
             .get(&(String::from("https://avd.aliyun.com/detail?id=") + id));
         match resp {
             Ok(v) => {
-                cve = self.parser_html(v.text().unwrap().as_str());
+                cve = parser_html(v.text().unwrap().as_str());
+                report_missing(id, &cve);
             }
             Err(err) => {
                 println!("{:#?}", err);
             }
         }
-        Box::new(cve)
+        CveRecord::from(cve)
     }
     fn id(&self) -> String {
         String::from(ALI_YUN_CVE_API)
     }
+
+    fn remote(&self) -> bool {
+        true
+    }
 }
 
 
@@ -115,48 +80,6 @@ impl AsyncAliyunApi {
         }
     }
 
-    fn trim_node(&self, s: String) -> String {
-        s.trim().trim_matches('\n').to_string()
-    }
-
-    fn parser_html(&self, target: &str) -> AliyunCve {
-        let document = Document::from(target);
-        let mut cve = AliyunCve::new();
-        for node in document.find(Class("header__title__text")).take(1) {
-            cve.title = self.trim_node(node.text());
-        }
-        for node in document.find(Class("metric-value")).take(1) {
-            cve.id = self.trim_node(node.text());
-        }
-
-        for node in document.find(Class("metric-value")).take(3) {
-            cve.fix_label = self.trim_node(node.text());
-        }
-        for node in document.find(Class("metric-value")).take(4) {
-            cve.publish = self.trim_node(node.text());
-        }
-        for node in document
-            .find(Class("text-detail").child(Name("div")))
-            .collect::<Vec<Node>>()
-        {
-            cve.description += &format!("{}\n", self.trim_node(node.text()));
-        }
-        for node in document.find(Class("text-detail").and(Name("div"))).take(2) {
-            cve.suggestion = self.trim_node(node.text());
-        }
-        for node in document
-            .find(Class("cvss-breakdown").descendant(Class("cvss-breakdown__score")))
-            .take(2)
-        {
-            cve.score = self.trim_node(node.text());
-        }
-        for node in document.find(Class("cvss-breakdown__desc")).take(1) {
-            cve.effect = self.trim_node(node.text());
-        }
-        // println!("{:#?}", cve);
-        cve
-    }
-
     #[allow(unused)]
     fn write_file(&self, path: &str, response: reqwest::blocking::Response) {
         let mut file = fs::OpenOptions::new()
@@ -167,13 +90,14 @@ impl AsyncAliyunApi {
         file.write_all(&response.bytes().unwrap()).unwrap();
     }
 
-    pub async fn query(&self, id: &str) -> Result<Box<dyn Cve>, Box<dyn std::error::Error>> {
+    pub async fn query(&self, id: &str) -> Result<CveRecord, Box<dyn std::error::Error>> {
         let mut cve = AliyunCve::new();
         let resp = self
             .http_client
             .get(&(String::from("https://avd.aliyun.com/detail?id=") + id)).await?;
-        cve = self.parser_html(resp.text().await?.as_str());
-        Ok(Box::new(cve))
+        cve = parser_html(resp.text().await?.as_str());
+        report_missing(id, &cve);
+        Ok(CveRecord::from(cve))
     }
 }
 
@@ -183,7 +107,80 @@ impl Default for AsyncAliyunApi {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+lazy_static! {
+    static ref SELECTORS: RwLock<AliyunSelectors> = RwLock::new(AliyunSelectors::default());
+}
+
+/// Where `parser_html` finds each field of an AVD detail page, the `[aliyun]` table of the config file.
+/// Fields are found by the text of their label, heading or table header instead of their position.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AliyunSelectors {
+    /// Class of the title node.
+    pub title: String,
+    /// Classes of the label and value nodes of a metric, both children of one node.
+    pub metric_label: String,
+    pub metric_value: String,
+    pub id_labels: Vec<String>,
+    pub fix_label_labels: Vec<String>,
+    pub publish_labels: Vec<String>,
+    /// Class of the text sections, each found by the heading before it.
+    pub text_detail: String,
+    pub description_headings: Vec<String>,
+    pub suggestion_headings: Vec<String>,
+    pub reference_headings: Vec<String>,
+    /// Classes of the score and the severity of the first CVSS breakdown.
+    pub score: String,
+    pub effect: String,
+    /// Header of the first column of the CWE table.
+    pub cwe_header: String,
+    /// Headers of the vendor, product, version and range columns of the affected software table.
+    pub affected_headers: Vec<String>,
+}
+
+impl Default for AliyunSelectors {
+    fn default() -> Self {
+        let strings = |v: &[&str]| v.iter().map(|x| x.to_string()).collect();
+        AliyunSelectors {
+            title: String::from("header__title__text"),
+            metric_label: String::from("metric-label"),
+            metric_value: String::from("metric-value"),
+            id_labels: strings(&["CVE编号"]),
+            fix_label_labels: strings(&["补丁情况"]),
+            publish_labels: strings(&["披露时间"]),
+            text_detail: String::from("text-detail"),
+            description_headings: strings(&["漏洞描述"]),
+            suggestion_headings: strings(&["解决建议"]),
+            reference_headings: strings(&["参考链接"]),
+            score: String::from("cvss-breakdown__score"),
+            effect: String::from("cvss-breakdown__desc"),
+            cwe_header: String::from("CWE-ID"),
+            affected_headers: strings(&["厂商", "产品", "版本", "影响面"]),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    aliyun: AliyunSelectors,
+}
+
+impl AliyunSelectors {
+    /// Reads the `[aliyun]` table of a TOML config file, missing keys keep their default.
+    pub fn load(path: &str) -> Result<AliyunSelectors, Box<dyn std::error::Error>> {
+        let data = fs::read_to_string(path)?;
+        let config: ConfigFile = toml::from_str(&data)?;
+        Ok(config.aliyun)
+    }
+}
+
+/// Sets the selectors used by every following `parser_html`.
+pub fn configure(selectors: AliyunSelectors) {
+    *SELECTORS.write().unwrap() = selectors;
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 struct AliyunCve {
     id: String,
     title: String,
@@ -193,40 +190,202 @@ struct AliyunCve {
     suggestion: String,
     score: String,
     effect: String,
+    references: Vec<String>,
+    cwe: Vec<String>,
+    affected: Vec<String>,
+    /// The fields not found on the page.
+    #[serde(skip)]
+    missing: Vec<&'static str>,
 }
 
 impl AliyunCve {
     fn new() -> AliyunCve {
-        AliyunCve {
-            id: String::new(),
-            title: String::new(),
-            fix_label: String::new(),
-            publish: String::new(),
-            description: String::new(),
-            suggestion: String::new(),
-            score: String::new(),
-            effect: String::new(),
+        AliyunCve::default()
+    }
+}
+
+fn trim_node(s: String) -> String {
+    s.trim().trim_matches('\n').to_string()
+}
+
+fn parser_html(target: &str) -> AliyunCve {
+    parser_html_with(target, &SELECTORS.read().unwrap())
+}
+
+/// The record of an AVD detail page, for callers fetching pages themselves like `AvdSearch`.
+pub(crate) fn parse_detail(target: &str) -> CveRecord {
+    CveRecord::from(parser_html(target))
+}
+
+/// The one parser of AVD detail pages shared by `AliyunApi` and `AsyncAliyunApi`.
+fn parser_html_with(target: &str, selectors: &AliyunSelectors) -> AliyunCve {
+    let document = Document::from(target);
+    let mut cve = AliyunCve::new();
+    if let Some(node) = document.find(Class(selectors.title.as_str())).next() {
+        cve.title = trim_node(node.text());
+    }
+
+    for label in document.find(Class(selectors.metric_label.as_str())) {
+        let value = match label
+            .parent()
+            .and_then(|x| x.find(Class(selectors.metric_value.as_str())).next())
+        {
+            Some(v) => trim_node(v.text()),
+            None => continue,
+        };
+        let label = trim_node(label.text());
+        if selectors.id_labels.contains(&label) {
+            cve.id = value;
+        } else if selectors.fix_label_labels.contains(&label) {
+            cve.fix_label = value;
+        } else if selectors.publish_labels.contains(&label) {
+            cve.publish = value;
+        }
+    }
+
+    for node in document.find(Class(selectors.text_detail.as_str())) {
+        let heading = heading_before(node);
+        if selectors.description_headings.contains(&heading) {
+            let lines: Vec<String> = node
+                .children()
+                .filter(|x| x.is(Name("div")))
+                .map(|x| trim_node(x.text()))
+                .collect();
+            cve.description = if lines.is_empty() {
+                trim_node(node.text())
+            } else {
+                lines.join("\n")
+            };
+        } else if selectors.suggestion_headings.contains(&heading) {
+            cve.suggestion = trim_node(node.text());
+        } else if selectors.reference_headings.contains(&heading) {
+            cve.references = node
+                .find(Name("a"))
+                .filter_map(|x| x.attr("href"))
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect();
         }
     }
+
+    if let Some(node) = document.find(Class(selectors.score.as_str())).next() {
+        cve.score = trim_node(node.text());
+    }
+    if let Some(node) = document.find(Class(selectors.effect.as_str())).next() {
+        cve.effect = trim_node(node.text());
+    }
+
+    for table in document.find(Name("table")) {
+        let headers: Vec<String> = table.find(Name("th")).map(|x| trim_node(x.text())).collect();
+        if headers.is_empty() {
+            continue;
+        }
+        let rows: Vec<Vec<String>> = table
+            .find(Name("tr"))
+            .map(|x| x.find(Name("td")).map(|x| trim_node(x.text())).collect::<Vec<String>>())
+            .filter(|x| !x.is_empty())
+            .collect();
+        if headers[0] == selectors.cwe_header {
+            cve.cwe = rows
+                .iter()
+                .filter_map(|x| x.first())
+                .filter(|x| !x.is_empty())
+                .cloned()
+                .collect();
+        } else if let Some(columns) = affected_columns(&headers, &selectors.affected_headers) {
+            cve.affected = rows.iter().filter_map(|x| affected_line(x, &columns)).collect();
+        }
+    }
+
+    let fields = [
+        ("id", cve.id.is_empty()),
+        ("title", cve.title.is_empty()),
+        ("fix_label", cve.fix_label.is_empty()),
+        ("publish", cve.publish.is_empty()),
+        ("description", cve.description.is_empty()),
+        ("suggestion", cve.suggestion.is_empty()),
+        ("score", cve.score.is_empty()),
+        ("effect", cve.effect.is_empty()),
+        ("references", cve.references.is_empty()),
+        ("cwe", cve.cwe.is_empty()),
+        ("affected", cve.affected.is_empty()),
+    ];
+    cve.missing = fields.iter().filter(|x| x.1).map(|x| x.0).collect();
+    cve
+}
+
+/// The text of the element before `node`, e.g. the `漏洞描述` heading of a text section.
+fn heading_before(node: Node) -> String {
+    let mut prev = node.prev();
+    while let Some(v) = prev {
+        if v.name().is_some() {
+            let text = trim_node(v.text());
+            if !text.is_empty() {
+                return text;
+            }
+        }
+        prev = v.prev();
+    }
+    String::new()
+}
+
+/// The column index of every affected header, `None` unless the vendor and product columns exist.
+fn affected_columns(headers: &[String], affected_headers: &[String]) -> Option<Vec<Option<usize>>> {
+    let columns: Vec<Option<usize>> = affected_headers
+        .iter()
+        .map(|x| headers.iter().position(|h| h == x))
+        .collect();
+    if columns.len() < 2 || columns[0].is_none() || columns[1].is_none() {
+        return None;
+    }
+    Some(columns)
 }
 
-impl Cve for AliyunCve {
-    fn to_json(&self) -> std::string::String {
-        serde_json::to_string(&self).unwrap()
+/// `vendor product: version range`, skipping the `*` any version placeholder.
+fn affected_line(row: &[String], columns: &[Option<usize>]) -> Option<String> {
+    let cell = |column: &Option<usize>| -> String {
+        column
+            .and_then(|x| row.get(x))
+            .map(|x| x.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|x| x != "*")
+            .unwrap_or_default()
+    };
+    let product = columns[..2].iter().map(cell).filter(|x| !x.is_empty()).collect::<Vec<String>>().join(" ");
+    if product.is_empty() {
+        return None;
+    }
+    let ranges = columns[2..].iter().map(cell).filter(|x| !x.is_empty()).collect::<Vec<String>>().join(" ");
+    if ranges.is_empty() {
+        Some(product)
+    } else {
+        Some(format!("{}: {}", product, ranges))
     }
+}
 
-    fn get(&self, key: &str) -> String {
-        match key {
-            "id" => self.id.clone(),
-            "title" => self.title.clone(),
-            "fix_label" => self.fix_label.clone(),
-            "publish" => self.publish.clone(),
-            "description" => self.description.clone(),
-            "suggestion" => self.suggestion.clone(),
-            "score" => self.score.clone(),
-            "effect" => self.effect.clone(),
-            _ => String::new(),
+/// Prints the fields a found page lacked, a hint that the page layout or the selectors changed.
+fn report_missing(id: &str, cve: &AliyunCve) {
+    if !cve.title.is_empty() && !cve.missing.is_empty() {
+        println!("{} aliyun page missing fields: {}", id, cve.missing.join(", "));
+    }
+}
+
+impl From<AliyunCve> for CveRecord {
+    fn from(cve: AliyunCve) -> Self {
+        let mut record = CveRecord::new();
+        record.id = cve.id;
+        record.title = cve.title;
+        record.fix_label = cve.fix_label;
+        record.published = parse_date(&cve.publish);
+        record.description = cve.description.trim().to_string();
+        record.suggestion = cve.suggestion;
+        if let Some(score) = parse_score(&cve.score) {
+            record.cvss.push(CvssMetric::new("", "", Some(score)));
         }
+        record.severity = Severity::parse(&cve.effect);
+        record.cwe = cve.cwe;
+        record.affected = cve.affected;
+        record.references = cve.references;
+        record
     }
 }
 
@@ -245,8 +404,77 @@ mod tests {
 
     #[test]
     fn test_cve_parser() {
-        let _cve_api = AliyunApi::new();
-        //cve_api.parser_html(include_str!("cve.html"));
+        let cve = parser_html_with(
+            include_str!("testdata/aliyun/CVE-2023-25194.html"),
+            &AliyunSelectors::default(),
+        );
+        assert!(cve.missing.is_empty(), "{:?}", cve.missing);
+        let record = CveRecord::from(cve);
+        assert_eq!(record.id, "CVE-2023-25194");
+        assert_eq!(record.title, "Apache Kafka Connect JNDI注入漏洞");
+        assert_eq!(record.fix_label, "官方补丁");
+        assert_eq!(record.published.unwrap().to_string(), "2023-02-07");
+        assert!(record.description.starts_with("A possible security vulnerability"));
+        assert_eq!(record.description.lines().count(), 2);
+        assert_eq!(record.suggestion, "升级 Apache Kafka 至 3.4.0 或更高版本。");
+        assert_eq!(record.score(), Some(8.8));
+        assert_eq!(record.severity(), Some(Severity::High));
+        assert_eq!(record.cwe, vec!["CWE-502"]);
+        assert_eq!(
+            record.affected,
+            vec!["apache kafka: From (including) 2.3.0 Up to (including) 3.3.2"]
+        );
+        assert_eq!(record.references.len(), 2);
+        assert_eq!(record.references[0], "https://kafka.apache.org/cve-list");
+    }
+
+    #[test]
+    fn test_cve_parser_layout() {
+        // the metrics are reordered and the page has no suggestion
+        let cve = parser_html_with(
+            include_str!("testdata/aliyun/CVE-2021-44228.html"),
+            &AliyunSelectors::default(),
+        );
+        assert_eq!(cve.missing, vec!["suggestion"]);
+        assert_eq!(cve.fix_label, "官方补丁");
+        assert_eq!(cve.publish, "2021-12-10");
+        assert_eq!(cve.cwe, vec!["CWE-20", "CWE-502"]);
+        assert_eq!(
+            cve.affected,
+            vec![
+                "apache log4j: From (including) 2.0.1 Up to (excluding) 2.3.1",
+                "apache log4j: 2.0"
+            ]
+        );
+        let record = CveRecord::from(cve);
+        assert_eq!(record.severity(), Some(Severity::Critical));
+
+        let cve = parser_html_with(
+            include_str!("testdata/aliyun/not-found.html"),
+            &AliyunSelectors::default(),
+        );
+        assert_eq!(cve.missing.len(), 11);
+        assert!(CveRecord::from(cve).is_empty());
+    }
+
+    #[test]
+    fn test_selectors() {
+        let config: ConfigFile = toml::from_str(
+            r#"
+            [http]
+            retries = 1
+            [aliyun]
+            fix_label_labels = ["修复情况"]
+            "#,
+        )
+        .unwrap();
+        let selectors = config.aliyun;
+        assert_eq!(selectors.fix_label_labels, vec!["修复情况"]);
+        assert_eq!(selectors.publish_labels, AliyunSelectors::default().publish_labels);
+
+        let page = include_str!("testdata/aliyun/CVE-2023-25194.html").replace("补丁情况", "修复情况");
+        assert_eq!(parser_html_with(&page, &AliyunSelectors::default()).missing, vec!["fix_label"]);
+        assert_eq!(parser_html_with(&page, &selectors).fix_label, "官方补丁");
     }
 
     #[test]
@@ -0,0 +1,234 @@
+use crate::command::cve::api::aliyun_api::parse_detail;
+use crate::command::cve::api::base::AsyncHttpClient;
+use crate::command::cve::api::record::CveRecord;
+use crate::command::cve::api::version::VersionRange;
+use select::document::Document;
+use select::predicate::Name;
+use std::error::Error;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+pub const AVD_URL: &str = "https://avd.aliyun.com";
+
+/// Finds the CVEs of a product through the AVD search listing and the detail page of every hit.
+pub struct AvdSearch {
+    http_client: AsyncHttpClient,
+    base_url: String,
+}
+
+/// One row of the AVD search listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvdHit {
+    /// The AVD id of the detail link, e.g. `AVD-2023-25194`.
+    pub avd: String,
+    /// The CVE id shown in the row, empty when the row has none.
+    pub cve: String,
+    pub title: String,
+}
+
+impl AvdSearch {
+    pub fn new(base_url: &str) -> AvdSearch {
+        AvdSearch {
+            http_client: AsyncHttpClient::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The hits of one listing page, empty past the last page.
+    pub async fn list(&self, product: &str, page: usize) -> Result<Vec<AvdHit>, Box<dyn Error>> {
+        let url = reqwest::Url::parse_with_params(
+            &format!("{}/search", self.base_url),
+            &[("q", product), ("page", &page.to_string())],
+        )?;
+        let resp = self.http_client.get(url.as_str()).await?;
+        Ok(parser_search_html(resp.text().await?.as_str()))
+    }
+
+    pub async fn detail(&self, id: &str) -> Result<CveRecord, Box<dyn Error>> {
+        let resp = self
+            .http_client
+            .get(&format!("{}/detail?id={}", self.base_url, id))
+            .await?;
+        Ok(parse_detail(resp.text().await?.as_str()))
+    }
+
+    /// The CVEs of `product` whose affected versions overlap `range`, reading at most `pages`
+    /// listing pages and `jobs` detail pages at a time.
+    pub async fn search(
+        self: &Arc<Self>,
+        product: &str,
+        range: &VersionRange,
+        pages: usize,
+        jobs: usize,
+    ) -> Result<Vec<CveRecord>, Box<dyn Error>> {
+        let mut hits: Vec<AvdHit> = Vec::new();
+        for page in 1..=pages {
+            let list = self.list(product, page).await?;
+            println!("{} page {}: {} hits", product, page, list.len());
+            if list.is_empty() {
+                break;
+            }
+            for hit in list {
+                if !hits.iter().any(|x| x.avd == hit.avd) {
+                    hits.push(hit);
+                }
+            }
+        }
+
+        let semaphore = Arc::new(Semaphore::new(jobs.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, hit) in hits.iter().cloned().enumerate() {
+            let permit = semaphore.clone().acquire_owned().await.unwrap();
+            let search = self.clone();
+            tasks.spawn(async move {
+                // a row without a CVE id is fetched by its AVD id, the page names the CVE
+                let id = if hit.cve.is_empty() { &hit.avd } else { &hit.cve };
+                let ret = search.detail(id).await.map_err(|e| e.to_string());
+                drop(permit);
+                (index, hit, ret)
+            });
+        }
+
+        let mut found = Vec::new();
+        while let Some(ret) = tasks.join_next().await {
+            match ret {
+                Ok((index, hit, Ok(mut record))) => {
+                    if record.id.is_empty() {
+                        record.id = hit.cve.clone();
+                    }
+                    if record.title.is_empty() {
+                        record.title = hit.title.clone();
+                    }
+                    if record.id.starts_with("CVE-") && is_affected(&record, product, range) {
+                        found.push((index, record));
+                    }
+                }
+                Ok((_, hit, Err(e))) => println!("{} {}", hit.avd, e),
+                Err(e) => println!("{:#?}", e),
+            }
+        }
+        found.sort_by_key(|x| x.0);
+        Ok(found.into_iter().map(|x| x.1).collect())
+    }
+}
+
+/// The rows of the listing table that link to a detail page.
+pub fn parser_search_html(target: &str) -> Vec<AvdHit> {
+    let document = Document::from(target);
+    let mut hits = Vec::new();
+    for row in document.find(Name("tr")) {
+        let avd = match row
+            .find(Name("a"))
+            .filter_map(|x| x.attr("href"))
+            .find_map(|x| x.split_once("detail?id="))
+        {
+            Some((_, id)) => id.trim().to_string(),
+            None => continue,
+        };
+        let text = row.text();
+        let cve = text
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .find(|x| x.starts_with("CVE-"))
+            .unwrap_or_default()
+            .to_string();
+        let title = row
+            .find(Name("td"))
+            .nth(1)
+            .map(|x| x.text().trim().to_string())
+            .unwrap_or_default();
+        hits.push(AvdHit { avd, cve, title });
+    }
+    hits
+}
+
+/// True when an affected product of `record` has versions in `range`. A record without affected
+/// software, or with ranges that do not parse, is kept since it can not be ruled out.
+fn is_affected(record: &CveRecord, product: &str, range: &VersionRange) -> bool {
+    if range.is_any() || record.affected.is_empty() {
+        return true;
+    }
+    let product = product.to_lowercase();
+    let lines: Vec<(&str, &str)> = record
+        .affected
+        .iter()
+        .map(|x| x.split_once(": ").unwrap_or((x.as_str(), "")))
+        .collect();
+    let mut matched: Vec<&(&str, &str)> = lines
+        .iter()
+        .filter(|x| x.0.to_lowercase().contains(&product))
+        .collect();
+    if matched.is_empty() {
+        // AVD may name the product differently, e.g. log4j for log4j2
+        matched = lines.iter().collect();
+    }
+    matched.iter().any(|x| match VersionRange::parse_avd(x.1) {
+        Some(affected) => affected.intersects(range),
+        None => true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use std::io::{Read, Write};
+
+    /// Serves the recorded AVD pages, like avd.aliyun.com would.
+    fn serve_recorded_pages() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let (status, body) = match path {
+                    "/search?q=kafka&page=1" => ("200 OK", include_str!("testdata/aliyun/search-kafka-1.html")),
+                    "/search?q=kafka&page=2" => ("200 OK", include_str!("testdata/aliyun/search-kafka-2.html")),
+                    "/search?q=kafka&page=3" => ("200 OK", include_str!("testdata/aliyun/search-empty.html")),
+                    "/detail?id=CVE-2023-25194" => ("200 OK", include_str!("testdata/aliyun/CVE-2023-25194.html")),
+                    "/detail?id=CVE-2018-1288" => ("200 OK", include_str!("testdata/aliyun/CVE-2018-1288.html")),
+                    "/detail?id=AVD-2024-31141" => ("200 OK", include_str!("testdata/aliyun/CVE-2024-31141.html")),
+                    _ => ("404 Not Found", ""),
+                };
+                let resp = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(resp.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_parser_search_html() {
+        let hits = parser_search_html(include_str!("testdata/aliyun/search-kafka-1.html"));
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].avd, "AVD-2023-25194");
+        assert_eq!(hits[0].cve, "CVE-2023-25194");
+        assert_eq!(hits[0].title, "Apache Kafka Connect JNDI注入漏洞");
+        assert!(parser_search_html(include_str!("testdata/aliyun/search-empty.html")).is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        let url = serve_recorded_pages();
+        let search = Arc::new(AvdSearch::new(&url));
+        tokio_test::block_on(async {
+            let range = VersionRange::parse("3.0.0").unwrap();
+            let records = search.search("kafka", &range, 5, 2).await.unwrap();
+            let ids: Vec<&str> = records.iter().map(|x| x.id.as_str()).collect();
+            assert_eq!(ids, vec!["CVE-2023-25194", "CVE-2024-31141"]);
+
+            let records = search.search("kafka", &VersionRange::default(), 1, 2).await.unwrap();
+            let ids: Vec<&str> = records.iter().map(|x| x.id.as_str()).collect();
+            assert_eq!(ids, vec!["CVE-2023-25194", "CVE-2018-1288"]);
+        });
+    }
+}
@@ -1,27 +1,285 @@
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 extern crate rand;
 
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Mutex, RwLock};
 use rand::Rng;
-use reqwest::header::{HeaderValue, USER_AGENT};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER, USER_AGENT};
+use reqwest::{Certificate, NoProxy, Proxy, StatusCode};
+use serde::Deserialize;
+
+/// Settings shared by every `HttpClient` and `AsyncHttpClient`, set once from the command line.
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    /// Refuse every request instead of touching the network.
+    pub offline: bool,
+    /// How often a failed or throttled request is repeated.
+    pub retries: u32,
+    /// The first retry delay, doubled on every further attempt.
+    pub backoff: Duration,
+    /// Upper bound for retry delays, including the ones asked for by Retry-After.
+    pub max_backoff: Duration,
+    /// Requests per second per host, 0 for no limit.
+    pub rps: f64,
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    /// Comma separated hosts or domains that bypass the proxy.
+    pub no_proxy: Option<String>,
+    /// Proxy basic auth as `user:password`.
+    pub proxy_auth: Option<String>,
+    /// PEM bundle of extra root certificates.
+    pub ca_cert: Option<String>,
+    /// Accept invalid or self-signed server certificates.
+    pub insecure: bool,
+    pub connect_timeout: Duration,
+    pub timeout: Duration,
+}
+
+/// The `[http]` table of the config file, every key optional.
+#[derive(Debug, Default, Deserialize)]
+pub struct HttpConfig {
+    pub proxy: Option<String>,
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    pub no_proxy: Option<String>,
+    pub proxy_auth: Option<String>,
+    pub ca_cert: Option<String>,
+    pub insecure: Option<bool>,
+    /// Seconds.
+    pub connect_timeout: Option<u64>,
+    /// Seconds.
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+    pub rps: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    http: HttpConfig,
+}
+
+impl HttpConfig {
+    /// Reads the `[http]` table of a TOML config file.
+    pub fn load(path: &str) -> Result<HttpConfig, Box<dyn Error>> {
+        let data = std::fs::read_to_string(path)?;
+        let config: ConfigFile = toml::from_str(&data)?;
+        Ok(config.http)
+    }
+}
+
+impl HttpOptions {
+    /// Takes the proxy settings from HTTP_PROXY, HTTPS_PROXY and NO_PROXY (either case).
+    pub fn apply_env(&mut self) {
+        let env = |key: &str| {
+            std::env::var(key)
+                .or_else(|_| std::env::var(key.to_lowercase()))
+                .ok()
+                .filter(|x| !x.trim().is_empty())
+        };
+        if let Some(v) = env("HTTP_PROXY") {
+            self.http_proxy = Some(v);
+        }
+        if let Some(v) = env("HTTPS_PROXY") {
+            self.https_proxy = Some(v);
+        }
+        if let Some(v) = env("NO_PROXY") {
+            self.no_proxy = Some(v);
+        }
+    }
+
+    /// Overrides every setting the config file gives.
+    pub fn apply_config(&mut self, config: HttpConfig) {
+        if let Some(v) = config.proxy {
+            self.http_proxy = Some(v.clone());
+            self.https_proxy = Some(v);
+        }
+        if config.http_proxy.is_some() {
+            self.http_proxy = config.http_proxy;
+        }
+        if config.https_proxy.is_some() {
+            self.https_proxy = config.https_proxy;
+        }
+        if config.no_proxy.is_some() {
+            self.no_proxy = config.no_proxy;
+        }
+        if config.proxy_auth.is_some() {
+            self.proxy_auth = config.proxy_auth;
+        }
+        if config.ca_cert.is_some() {
+            self.ca_cert = config.ca_cert;
+        }
+        if let Some(v) = config.insecure {
+            self.insecure = v;
+        }
+        if let Some(v) = config.connect_timeout {
+            self.connect_timeout = Duration::from_secs(v);
+        }
+        if let Some(v) = config.timeout {
+            self.timeout = Duration::from_secs(v);
+        }
+        if let Some(v) = config.retries {
+            self.retries = v;
+        }
+        if let Some(v) = config.rps {
+            self.rps = v;
+        }
+    }
+
+    /// The configured proxies, none unless a proxy was set explicitly.
+    pub fn proxies(&self) -> Result<Vec<Proxy>, Box<dyn Error>> {
+        let mut proxies = Vec::new();
+        for (url, is_https) in [(&self.http_proxy, false), (&self.https_proxy, true)] {
+            if let Some(url) = url {
+                let mut proxy = if is_https {
+                    Proxy::https(url)?
+                } else {
+                    Proxy::http(url)?
+                };
+                if let Some(auth) = &self.proxy_auth {
+                    let (user, password) = auth.split_once(':').unwrap_or((auth, ""));
+                    proxy = proxy.basic_auth(user, password);
+                }
+                if let Some(no_proxy) = &self.no_proxy {
+                    proxy = proxy.no_proxy(NoProxy::from_string(no_proxy));
+                }
+                proxies.push(proxy);
+            }
+        }
+        Ok(proxies)
+    }
+
+    pub fn ca_certificate(&self) -> Result<Option<Certificate>, Box<dyn Error>> {
+        match &self.ca_cert {
+            Some(path) => Ok(Some(Certificate::from_pem(&std::fs::read(path)?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Exponential backoff with jitter, between half and all of `backoff * 2^attempt`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        let half = delay / 2;
+        half + half.mul_f64(rand::thread_rng().gen::<f64>())
+    }
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        HttpOptions {
+            offline: false,
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            rps: 0.0,
+            http_proxy: None,
+            https_proxy: None,
+            no_proxy: None,
+            proxy_auth: None,
+            ca_cert: None,
+            insecure: false,
+            connect_timeout: Duration::from_secs(2),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+lazy_static! {
+    static ref HTTP_OPTIONS: RwLock<HttpOptions> = RwLock::new(HttpOptions::default());
+    static ref RATE_LIMITER: RateLimiter = RateLimiter::new();
+}
+
+/// Spaces requests to the same host, shared by the blocking and the async client.
+struct RateLimiter {
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    fn new() -> RateLimiter {
+        RateLimiter {
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserves the next request slot for the host of `url` and returns how long to wait for it.
+    fn reserve(&self, url: &str, rps: f64) -> Duration {
+        if rps <= 0.0 {
+            return Duration::ZERO;
+        }
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|x| x.host_str().map(|x| x.to_string()))
+            .unwrap_or_default();
+        let interval = Duration::from_secs_f64(1.0 / rps);
+        let now = Instant::now();
+        let mut next = self.next.lock().unwrap();
+        let slot = next.get(&host).copied().filter(|x| *x > now).unwrap_or(now);
+        next.insert(host, slot + interval);
+        slot - now
+    }
+}
+
+/// Throttling and temporary server errors worth another attempt.
+fn is_retry_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::SERVICE_UNAVAILABLE
+        || status == StatusCode::BAD_GATEWAY
+        || status == StatusCode::GATEWAY_TIMEOUT
+}
+
+/// The delay asked for by a Retry-After header, given in seconds or as an http date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+}
+
+/// Sets the options used by clients created afterwards.
+pub fn configure(options: HttpOptions) {
+    *HTTP_OPTIONS.write().unwrap() = options;
+}
+
+pub fn options() -> HttpOptions {
+    HTTP_OPTIONS.read().unwrap().clone()
+}
+
+fn offline_error(url: &str) -> Box<dyn std::error::Error> {
+    format!("offline mode, refused to request {}", url).into()
+}
+
 pub struct HttpClient {
     client: reqwest::blocking::Client,
     user_agent: Vec<String>,
+    options: HttpOptions,
 }
 
 impl HttpClient {
     pub fn new() -> HttpClient {
-        let proxy = reqwest::Proxy::http("http://116.9.163.205:58080").unwrap();
-        let client = reqwest::blocking::Client::builder()
+        let options = options();
+        let mut builder = reqwest::blocking::Client::builder()
             .pool_max_idle_per_host(50)
             .pool_idle_timeout(Duration::from_secs(2))
-            .connect_timeout(Duration::from_secs(2))
-            .timeout(Duration::from_secs(10))
-            .proxy(proxy.clone())
-            .build()
-            .unwrap();
+            .connect_timeout(options.connect_timeout)
+            .timeout(options.timeout)
+            .danger_accept_invalid_certs(options.insecure)
+            .no_proxy();
+        for proxy in options.proxies().unwrap() {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(cert) = options.ca_certificate().unwrap() {
+            builder = builder.add_root_certificate(cert);
+        }
+        let client = builder.build().unwrap();
         let user_agent = HttpClient::read_user_agent("");
 
-        HttpClient {client, user_agent }
+        HttpClient {client, user_agent, options }
     }
 
     fn read_user_agent(path: &str) -> Vec<String> {
@@ -50,17 +308,39 @@ impl HttpClient {
         &self,
         url: &str
     ) -> Result<reqwest::blocking::Response, Box<dyn std::error::Error>> {
-        let user_agent = match self.random_user_agent() {
-            Some(v) => v,
-            None => "",
-        };
+        if self.options.offline {
+            return Err(offline_error(url));
+        }
+        let mut attempt = 0;
+        loop {
+            std::thread::sleep(RATE_LIMITER.reserve(url, self.options.rps));
+            let user_agent = match self.random_user_agent() {
+                Some(v) => v,
+                None => "",
+            };
 
-        let resp = self
-            .client
-            .get(url)
-            .header(USER_AGENT, HeaderValue::from_str(user_agent).unwrap())
-            .send()?;
-        Ok(resp)
+            let resp = self
+                .client
+                .get(url)
+                .header(USER_AGENT, HeaderValue::from_str(user_agent).unwrap())
+                .send();
+            let delay = match resp {
+                Ok(resp) if attempt < self.options.retries && is_retry_status(resp.status()) => {
+                    println!("{} {}, retry {}", url, resp.status(), attempt + 1);
+                    retry_after(resp.headers())
+                        .map(|x| x.min(self.options.max_backoff))
+                        .unwrap_or_else(|| self.options.backoff(attempt))
+                }
+                Ok(resp) => return Ok(resp),
+                Err(e) if attempt < self.options.retries && !e.is_builder() => {
+                    println!("{} {}, retry {}", url, e, attempt + 1);
+                    self.options.backoff(attempt)
+                }
+                Err(e) => return Err(e.into()),
+            };
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
     }
 }
 
@@ -74,21 +354,28 @@ impl Default for HttpClient {
 pub struct AsyncHttpClient {
     async_client: reqwest::Client,
     user_agent: Vec<String>,
+    options: HttpOptions,
 }
 
 impl AsyncHttpClient {
     pub fn new() -> AsyncHttpClient {
-        let proxy = reqwest::Proxy::http("http://116.9.163.205:58080").unwrap();
+        let options = options();
         let user_agent = crate::command::cve::api::base::HttpClient::read_user_agent("");
-        let async_client = reqwest::ClientBuilder::new()
+        let mut builder = reqwest::ClientBuilder::new()
             .pool_max_idle_per_host(50)
             .pool_idle_timeout(Duration::from_secs(2))
-            .connect_timeout(Duration::from_secs(2))
-            .timeout(Duration::from_secs(10))
-            .proxy(proxy)
-            .build()
-            .unwrap();
-        AsyncHttpClient {async_client, user_agent}
+            .connect_timeout(options.connect_timeout)
+            .timeout(options.timeout)
+            .danger_accept_invalid_certs(options.insecure)
+            .no_proxy();
+        for proxy in options.proxies().unwrap() {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(cert) = options.ca_certificate().unwrap() {
+            builder = builder.add_root_certificate(cert);
+        }
+        let async_client = builder.build().unwrap();
+        AsyncHttpClient {async_client, user_agent, options}
     }
 
     fn read_user_agent(path: &str) -> Vec<String> {
@@ -115,17 +402,39 @@ impl AsyncHttpClient {
 
     pub async fn get(&self,
                            url: &str) -> Result<reqwest::Response,  Box<dyn std::error::Error>> {
-        let user_agent = match self.random_user_agent() {
-            Some(v) => v,
-            None => "",
-        };
+        if self.options.offline {
+            return Err(offline_error(url));
+        }
+        let mut attempt = 0;
+        loop {
+            tokio::time::sleep(RATE_LIMITER.reserve(url, self.options.rps)).await;
+            let user_agent = match self.random_user_agent() {
+                Some(v) => v,
+                None => "",
+            };
 
-        let resp = self
-            .async_client
-            .get(url)
-            .header(USER_AGENT, HeaderValue::from_str(user_agent).unwrap())
-            .send().await?;
-        Ok(resp)
+            let resp = self
+                .async_client
+                .get(url)
+                .header(USER_AGENT, HeaderValue::from_str(user_agent).unwrap())
+                .send().await;
+            let delay = match resp {
+                Ok(resp) if attempt < self.options.retries && is_retry_status(resp.status()) => {
+                    println!("{} {}, retry {}", url, resp.status(), attempt + 1);
+                    retry_after(resp.headers())
+                        .map(|x| x.min(self.options.max_backoff))
+                        .unwrap_or_else(|| self.options.backoff(attempt))
+                }
+                Ok(resp) => return Ok(resp),
+                Err(e) if attempt < self.options.retries && !e.is_builder() => {
+                    println!("{} {}, retry {}", url, e, attempt + 1);
+                    self.options.backoff(attempt)
+                }
+                Err(e) => return Err(e.into()),
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
     }
 }
 
@@ -149,6 +458,99 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_offline_get() {
+        let mut http_client = HttpClient::new();
+        http_client.options.offline = true;
+        let err = http_client.get("https://www.baidu.com").unwrap_err();
+        assert_eq!(err.to_string(), "offline mode, refused to request https://www.baidu.com");
+    }
+
+    #[test]
+    fn test_backoff() {
+        let options = HttpOptions::default();
+        for attempt in 0..4 {
+            let delay = options.backoff(attempt);
+            let full = Duration::from_millis(500 * 2u64.pow(attempt));
+            assert!(delay >= full / 2 && delay <= full);
+        }
+        assert!(options.backoff(20) <= options.max_backoff);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new();
+        assert_eq!(limiter.reserve("https://avd.aliyun.com/detail?id=1", 0.0), Duration::ZERO);
+        assert_eq!(limiter.reserve("https://avd.aliyun.com/detail?id=1", 2.0), Duration::ZERO);
+        let delay = limiter.reserve("https://avd.aliyun.com/detail?id=2", 2.0);
+        assert!(delay > Duration::from_millis(400) && delay <= Duration::from_millis(500));
+        assert_eq!(limiter.reserve("https://nvd.nist.gov/", 2.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_get_retry() {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/detail", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for status in ["503 Service Unavailable", "200 OK"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).unwrap();
+                let resp = format!("HTTP/1.1 {}\r\nRetry-After: 0\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok", status);
+                stream.write_all(resp.as_bytes()).unwrap();
+            }
+        });
+
+        let http_client = HttpClient {
+            client: reqwest::blocking::Client::new(),
+            user_agent: vec![String::from("etool")],
+            options: HttpOptions::default(),
+        };
+        let resp = http_client.get(&url).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_proxy_options() {
+        let mut options = HttpOptions::default();
+        assert_eq!(options.proxies().unwrap().len(), 0);
+
+        let config: ConfigFile = toml::from_str(
+            r#"
+            [http]
+            proxy = "http://proxy.example.com:3128"
+            https_proxy = "http://secure-proxy.example.com:3128"
+            no_proxy = "localhost,127.0.0.1"
+            proxy_auth = "user:password"
+            insecure = true
+            timeout = 30
+            "#,
+        )
+        .unwrap();
+        options.apply_config(config.http);
+        assert_eq!(options.http_proxy.as_deref(), Some("http://proxy.example.com:3128"));
+        assert_eq!(options.https_proxy.as_deref(), Some("http://secure-proxy.example.com:3128"));
+        assert!(options.insecure);
+        assert_eq!(options.timeout, Duration::from_secs(30));
+        assert_eq!(options.connect_timeout, Duration::from_secs(2));
+        assert_eq!(options.proxies().unwrap().len(), 2);
+
+        options.http_proxy = Some(String::from("not a url"));
+        assert!(options.proxies().is_err());
+    }
+
     #[test]
     fn test_async_get() -> Result<(), Box<dyn std::error::Error>> {
         // let http_client = HttpClient::new();
@@ -0,0 +1,140 @@
+use crate::command::cve::api::record::CveRecord;
+use serde::{self, Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Persistent CVE detail cache, one `<dir>/<provider>/<id>.json` file per entry.
+pub struct CveCache {
+    dir: PathBuf,
+    ttl: u64,
+    refresh: bool,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl CveCache {
+    /// `ttl` is in seconds, `refresh` ignores every stored entry but still writes new ones.
+    pub fn new(dir: &str, ttl: u64, refresh: bool) -> CveCache {
+        CveCache {
+            dir: PathBuf::from(dir),
+            ttl,
+            refresh,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    fn entry_path(&self, provider: &str, id: &str) -> PathBuf {
+        let name = id.replace(['/', '\\', ':'], "_");
+        self.dir.join(provider).join(format!("{}.json", name))
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs())
+            .unwrap_or_default()
+    }
+
+    /// Returns the stored answer of `provider` for `id` unless it is missing or expired.
+    pub fn get(&self, provider: &str, id: &str) -> Option<CveRecord> {
+        let cached = if self.refresh {
+            None
+        } else {
+            fs::read_to_string(self.entry_path(provider, id))
+                .ok()
+                .and_then(|x| serde_json::from_str::<CachedCve>(&x).ok())
+                .filter(|x| CveCache::now().saturating_sub(x.fetched_at) < self.ttl)
+        };
+        match cached {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        cached.map(|x| x.record)
+    }
+
+    pub fn put(&self, provider: &str, id: &str, cve: &CveRecord) {
+        if cve.is_empty() {
+            // nothing worth keeping, most likely a failed lookup
+            return;
+        }
+        let cached = CachedCve {
+            provider: provider.to_string(),
+            id: id.to_string(),
+            fetched_at: CveCache::now(),
+            record: cve.clone(),
+        };
+        let path = self.entry_path(provider, id);
+        let ret = fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| fs::write(&path, cached.to_json()));
+        if let Err(e) = ret {
+            println!("write cve cache {:?} error: {:#?}", path, e);
+        }
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn stats(&self) -> String {
+        format!(
+            "cve cache: {:?} hits: {} misses: {}",
+            self.dir,
+            self.hits(),
+            self.misses()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CachedCve {
+    provider: String,
+    id: String,
+    fetched_at: u64,
+    record: CveRecord,
+}
+
+impl CachedCve {
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_cache() {
+        let dir = std::env::temp_dir().join("etool-cache-test");
+        let cache = CveCache::new(dir.to_str().unwrap(), 3600, false);
+        assert!(cache.get("AliyunApi", "CVE-2023-25194").is_none());
+
+        cache.put("AliyunApi", "CVE-2023-25194", &CveRecord::new());
+        assert!(cache.get("AliyunApi", "CVE-2023-25194").is_none());
+
+        let mut record = CveRecord::new();
+        record.title = String::from("Apache Kafka Connect");
+        record.published = NaiveDate::from_ymd_opt(2023, 2, 7);
+        cache.put("AliyunApi", "CVE-2023-25194", &record);
+        let ret = cache.get("AliyunApi", "CVE-2023-25194").unwrap();
+        assert_eq!(ret, record);
+        assert!(cache.get("NvdApi", "CVE-2023-25194").is_none());
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+        // expired and refreshed entries are misses
+        let expired = CveCache::new(dir.to_str().unwrap(), 0, false);
+        assert!(expired.get("AliyunApi", "CVE-2023-25194").is_none());
+        let refresh = CveCache::new(dir.to_str().unwrap(), 3600, true);
+        assert!(refresh.get("AliyunApi", "CVE-2023-25194").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
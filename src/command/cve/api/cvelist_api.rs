@@ -0,0 +1,370 @@
+use crate::command::cve::api::lib::CveApi;
+use crate::command::cve::api::record::{parse_date, CveRecord, CvssMetric, Severity};
+use serde::{self, Deserialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CVE_LIST_API: &str = "CveListApi";
+
+/// Answers queries from a local checkout of the cvelistV5 repository,
+/// reading `cves/YYYY/NNxxx/CVE-YYYY-NNNNN.json` on demand.
+pub struct CveListApi {
+    root: PathBuf,
+}
+
+impl CveListApi {
+    /// `path` may point at the repository root or at its `cves` directory.
+    pub fn new(path: &str) -> CveListApi {
+        let mut root = PathBuf::from(path);
+        if root.join("cves").is_dir() {
+            root = root.join("cves");
+        }
+        CveListApi { root }
+    }
+
+    /// The record path for `id`, e.g. `cves/2023/25xxx/CVE-2023-25194.json`, `None` unless `id`
+    /// is `CVE-` a 4 digit year and a number, so it cannot point outside the repository.
+    fn record_path(&self, id: &str) -> Option<PathBuf> {
+        let is_digits = |x: &str| !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit());
+        let secs: Vec<&str> = id.split('-').collect();
+        if secs.len() != 3
+            || secs[0] != "CVE"
+            || secs[1].len() != 4
+            || !is_digits(secs[1])
+            || !is_digits(secs[2])
+        {
+            return None;
+        }
+        let number = secs[2].parse::<u64>().ok()?;
+        Some(
+            self.root
+                .join(secs[1])
+                .join(format!("{}xxx", number / 1000))
+                .join(format!("{}.json", id)),
+        )
+    }
+
+    fn read_record(path: &Path) -> Result<CveRecord, Box<dyn Error>> {
+        let data = fs::read_to_string(path)?;
+        CveListApi::parser_record(&data)
+    }
+
+    fn parser_record(data: &str) -> Result<CveRecord, Box<dyn Error>> {
+        let record: CveRecord5 = serde_json::from_str(data)?;
+        Ok(CveRecord::from(record))
+    }
+}
+
+impl CveApi for CveListApi {
+    fn query(&self, id: &str) -> CveRecord {
+        let mut cve = CveRecord::new();
+        if let Some(path) = self.record_path(id) {
+            if path.exists() {
+                match CveListApi::read_record(&path) {
+                    Ok(v) => cve = v,
+                    Err(e) => {
+                        println!("read cve record {:?} error: {:#?}", path, e);
+                    }
+                }
+            }
+        }
+        cve
+    }
+
+    fn id(&self) -> String {
+        String::from(CVE_LIST_API)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveRecord5 {
+    cve_metadata: CveMetadata,
+    #[serde(default)]
+    containers: CveContainers,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveMetadata {
+    cve_id: String,
+    #[serde(default)]
+    date_published: String,
+    #[serde(default)]
+    date_updated: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CveContainers {
+    #[serde(default)]
+    cna: CveContainer,
+    #[serde(default)]
+    adp: Vec<CveContainer>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveContainer {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    descriptions: Vec<CveLangValue>,
+    #[serde(default)]
+    solutions: Vec<CveLangValue>,
+    #[serde(default)]
+    rejected_reasons: Vec<CveLangValue>,
+    #[serde(default)]
+    affected: Vec<CveAffected>,
+    #[serde(default)]
+    problem_types: Vec<CveProblemType>,
+    #[serde(default)]
+    references: Vec<CveReference>,
+    #[serde(default)]
+    metrics: Vec<CveMetric>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CveLangValue {
+    lang: String,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveAffected {
+    #[serde(default)]
+    vendor: String,
+    #[serde(default)]
+    product: String,
+    #[serde(default)]
+    package_name: String,
+    #[serde(default)]
+    versions: Vec<CveVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveVersion {
+    version: String,
+    #[serde(default)]
+    status: String,
+    less_than: Option<String>,
+    less_than_or_equal: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CveProblemType {
+    #[serde(default)]
+    descriptions: Vec<CveProblemTypeDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveProblemTypeDescription {
+    #[serde(default)]
+    cwe_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CveReference {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CveMetric {
+    #[serde(rename = "cvssV4_0")]
+    cvss_v4_0: Option<CveCvss>,
+    #[serde(rename = "cvssV3_1")]
+    cvss_v3_1: Option<CveCvss>,
+    #[serde(rename = "cvssV3_0")]
+    cvss_v3_0: Option<CveCvss>,
+    #[serde(rename = "cvssV2_0")]
+    cvss_v2_0: Option<CveCvss>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveCvss {
+    #[serde(default)]
+    version: String,
+    base_score: f64,
+    #[serde(default)]
+    base_severity: String,
+    #[serde(default)]
+    vector_string: String,
+}
+
+impl CveContainer {
+    fn english(values: &[CveLangValue]) -> String {
+        values
+            .iter()
+            .find(|x| x.lang.starts_with("en"))
+            .or_else(|| values.first())
+            .map(|x| x.value.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    fn cvss(&self) -> Option<CveCvss> {
+        // prefer the v3 scores the Aliyun pages show, then v4 and v2
+        for metric in self.metrics.iter() {
+            if let Some(v) = metric.cvss_v3_1.as_ref().or(metric.cvss_v3_0.as_ref()) {
+                return Some(v.clone());
+            }
+        }
+        for metric in self.metrics.iter() {
+            if let Some(v) = metric.cvss_v4_0.as_ref().or(metric.cvss_v2_0.as_ref()) {
+                return Some(v.clone());
+            }
+        }
+        None
+    }
+}
+
+impl CveAffected {
+    fn describe(&self) -> String {
+        let mut ranges = Vec::new();
+        for version in self.versions.iter().filter(|x| x.status == "affected") {
+            let start = if version.version == "0" || version.version == "*" {
+                String::new()
+            } else {
+                format!(">={}", version.version)
+            };
+            let range = if let Some(v) = &version.less_than {
+                format!("{}, <{}", start, v)
+            } else if let Some(v) = &version.less_than_or_equal {
+                format!("{}, <={}", start, v)
+            } else {
+                version.version.clone()
+            };
+            ranges.push(range.trim_start_matches(", ").to_string());
+        }
+        let name = if self.product.is_empty() || self.product == "n/a" {
+            &self.package_name
+        } else {
+            &self.product
+        };
+        format!("{} {}: {}", self.vendor, name, ranges.join(" || "))
+            .trim()
+            .to_string()
+    }
+}
+
+impl From<CveRecord5> for CveRecord {
+    fn from(record: CveRecord5) -> Self {
+        let mut cve = CveRecord::new();
+        let cna = &record.containers.cna;
+        cve.id = record.cve_metadata.cve_id;
+        cve.published = parse_date(&record.cve_metadata.date_published);
+        cve.modified = parse_date(&record.cve_metadata.date_updated);
+        cve.description = CveContainer::english(&cna.descriptions);
+        if cve.description.is_empty() {
+            cve.description = CveContainer::english(&cna.rejected_reasons);
+        }
+        cve.title = if cna.title.is_empty() {
+            match cve.description.find(". ") {
+                Some(end) => cve.description[..end].to_string(),
+                None => cve.description.trim_end_matches('.').to_string(),
+            }
+        } else {
+            cna.title.clone()
+        };
+        cve.suggestion = CveContainer::english(&cna.solutions);
+
+        // the CNA is authoritative, ADP containers (e.g. CISA vulnrichment) fill the gaps
+        let containers: Vec<&CveContainer> = [cna].into_iter().chain(record.containers.adp.iter()).collect();
+        if let Some(cvss) = containers.iter().find_map(|x| x.cvss()) {
+            cve.cvss.push(CvssMetric::new(&cvss.version, &cvss.vector_string, Some(cvss.base_score)));
+            cve.severity = Severity::parse(&cvss.base_severity);
+        }
+
+        for container in containers.iter() {
+            for problem_type in container.problem_types.iter() {
+                for description in problem_type.descriptions.iter() {
+                    if !description.cwe_id.is_empty() && !cve.cwe.contains(&description.cwe_id) {
+                        cve.cwe.push(description.cwe_id.clone());
+                    }
+                }
+            }
+            for item in container.affected.iter() {
+                let line = item.describe();
+                if !cve.affected.contains(&line) {
+                    cve.affected.push(line);
+                }
+            }
+            for reference in container.references.iter() {
+                if !cve.references.contains(&reference.url) {
+                    cve.references.push(reference.url.clone());
+                }
+            }
+        }
+        cve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const CVE_RECORD_STR: &str = r#"{
+        "dataType": "CVE_RECORD",
+        "dataVersion": "5.1",
+        "cveMetadata": {"cveId": "CVE-2023-25194", "state": "PUBLISHED", "datePublished": "2023-02-07T19:53:21.000Z"},
+        "containers": {
+            "cna": {
+                "title": "Apache Kafka Connect API: Possible RCE/Denial of service attack via SASL JAAS JndiLoginModule configuration using Kafka Connect",
+                "descriptions": [{"lang": "en", "value": "A possible security vulnerability has been identified in Apache Kafka Connect API."}],
+                "affected": [{"vendor": "Apache Software Foundation", "product": "Apache Kafka Connect API", "versions": [{"version": "2.3.0", "status": "affected", "lessThanOrEqual": "3.3.2", "versionType": "semver"}]}],
+                "problemTypes": [{"descriptions": [{"lang": "en", "description": "CWE-502 Deserialization of Untrusted Data", "cweId": "CWE-502", "type": "CWE"}]}],
+                "references": [{"url": "https://hackerone.com/reports/1529790"}],
+                "solutions": [{"lang": "en", "value": "Upgrade to Apache Kafka 3.4.0 or later."}]
+            },
+            "adp": [{
+                "title": "CISA ADP Vulnrichment",
+                "metrics": [{"cvssV3_1": {"version": "3.1", "baseScore": 8.8, "baseSeverity": "HIGH", "vectorString": "CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:U/C:H/I:H/A:H"}}],
+                "references": [{"url": "https://hackerone.com/reports/1529790"}, {"url": "http://www.openwall.com/lists/oss-security/2023/02/07/1"}]
+            }]
+        }
+    }"#;
+
+    #[test]
+    fn test_record_path() {
+        let cve_api = CveListApi::new("./cvelistV5");
+        assert_eq!(
+            cve_api.record_path("CVE-2023-25194"),
+            Some(PathBuf::from("./cvelistV5/2023/25xxx/CVE-2023-25194.json"))
+        );
+        assert_eq!(
+            cve_api.record_path("CVE-2021-123"),
+            Some(PathBuf::from("./cvelistV5/2021/0xxx/CVE-2021-123.json"))
+        );
+        assert_eq!(cve_api.record_path("GHSA-69cg-p879-7622"), None);
+        assert_eq!(cve_api.record_path("CVE-..-1234"), None);
+        assert_eq!(cve_api.record_path("CVE-2023-+1234"), None);
+        assert_eq!(cve_api.record_path("CVE-/etc-1234"), None);
+    }
+
+    #[test]
+    fn test_query() {
+        let root = std::env::temp_dir().join("etool-cvelist-test");
+        let dir = root.join("cves/2023/25xxx");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("CVE-2023-25194.json"), CVE_RECORD_STR).unwrap();
+
+        let cve_api = CveListApi::new(root.to_str().unwrap());
+        let cve = cve_api.query("CVE-2023-25194");
+        assert_eq!(cve.published, parse_date("2023-02-07"));
+        assert_eq!(cve.score(), Some(8.8));
+        assert_eq!(cve.severity, Some(Severity::High));
+        assert_eq!(cve.cwe, vec!["CWE-502"]);
+        assert_eq!(cve.suggestion, "Upgrade to Apache Kafka 3.4.0 or later.");
+        assert_eq!(
+            cve.affected,
+            vec!["Apache Software Foundation Apache Kafka Connect API: >=2.3.0, <=3.3.2"]
+        );
+        assert_eq!(cve.references.len(), 2);
+        assert!(cve_api.query("CVE-2023-25195").is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,736 @@
+use crate::command::cve::api::record::Severity;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CvssVersion {
+    V2,
+    V3_0,
+    V3_1,
+    V4_0,
+}
+
+/// A validated CVSS v2, v3.0, v3.1 or v4.0 vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cvss {
+    version: CvssVersion,
+    metrics: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackVector {
+    Network,
+    Adjacent,
+    Local,
+    Physical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackComplexity {
+    Low,
+    /// CVSS v2 only.
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivilegesRequired {
+    None,
+    Low,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserInteraction {
+    None,
+    /// CVSS v3.
+    Required,
+    /// CVSS v4.
+    Passive,
+    /// CVSS v4.
+    Active,
+}
+
+/// Confidentiality, integrity or availability impact, CVSS v2 `Partial` and `Complete` map to `Low` and `High`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Impact {
+    None,
+    Low,
+    High,
+}
+
+/// Metric name, allowed values and whether the base score needs it.
+type MetricDef = (&'static str, &'static [&'static str], bool);
+
+const V2_METRICS: [MetricDef; 14] = [
+    ("AV", &["L", "A", "N"], true),
+    ("AC", &["H", "M", "L"], true),
+    ("Au", &["M", "S", "N"], true),
+    ("C", &["N", "P", "C"], true),
+    ("I", &["N", "P", "C"], true),
+    ("A", &["N", "P", "C"], true),
+    ("E", &["U", "POC", "F", "H", "ND"], false),
+    ("RL", &["OF", "TF", "W", "U", "ND"], false),
+    ("RC", &["UC", "UR", "C", "ND"], false),
+    ("CDP", &["N", "L", "LM", "MH", "H", "ND"], false),
+    ("TD", &["N", "L", "M", "H", "ND"], false),
+    ("CR", &["L", "M", "H", "ND"], false),
+    ("IR", &["L", "M", "H", "ND"], false),
+    ("AR", &["L", "M", "H", "ND"], false),
+];
+
+const V3_METRICS: [MetricDef; 22] = [
+    ("AV", &["N", "A", "L", "P"], true),
+    ("AC", &["L", "H"], true),
+    ("PR", &["N", "L", "H"], true),
+    ("UI", &["N", "R"], true),
+    ("S", &["U", "C"], true),
+    ("C", &["H", "L", "N"], true),
+    ("I", &["H", "L", "N"], true),
+    ("A", &["H", "L", "N"], true),
+    ("E", &["X", "U", "P", "F", "H"], false),
+    ("RL", &["X", "O", "T", "W", "U"], false),
+    ("RC", &["X", "U", "R", "C"], false),
+    ("CR", &["X", "L", "M", "H"], false),
+    ("IR", &["X", "L", "M", "H"], false),
+    ("AR", &["X", "L", "M", "H"], false),
+    ("MAV", &["X", "N", "A", "L", "P"], false),
+    ("MAC", &["X", "L", "H"], false),
+    ("MPR", &["X", "N", "L", "H"], false),
+    ("MUI", &["X", "N", "R"], false),
+    ("MS", &["X", "U", "C"], false),
+    ("MC", &["X", "N", "L", "H"], false),
+    ("MI", &["X", "N", "L", "H"], false),
+    ("MA", &["X", "N", "L", "H"], false),
+];
+
+const V4_METRICS: [MetricDef; 32] = [
+    ("AV", &["N", "A", "L", "P"], true),
+    ("AC", &["L", "H"], true),
+    ("AT", &["N", "P"], true),
+    ("PR", &["N", "L", "H"], true),
+    ("UI", &["N", "P", "A"], true),
+    ("VC", &["H", "L", "N"], true),
+    ("VI", &["H", "L", "N"], true),
+    ("VA", &["H", "L", "N"], true),
+    ("SC", &["H", "L", "N"], true),
+    ("SI", &["H", "L", "N"], true),
+    ("SA", &["H", "L", "N"], true),
+    ("E", &["X", "A", "P", "U"], false),
+    ("CR", &["X", "H", "M", "L"], false),
+    ("IR", &["X", "H", "M", "L"], false),
+    ("AR", &["X", "H", "M", "L"], false),
+    ("MAV", &["X", "N", "A", "L", "P"], false),
+    ("MAC", &["X", "L", "H"], false),
+    ("MAT", &["X", "N", "P"], false),
+    ("MPR", &["X", "N", "L", "H"], false),
+    ("MUI", &["X", "N", "P", "A"], false),
+    ("MVC", &["X", "H", "L", "N"], false),
+    ("MVI", &["X", "H", "L", "N"], false),
+    ("MVA", &["X", "H", "L", "N"], false),
+    ("MSC", &["X", "H", "L", "N"], false),
+    ("MSI", &["X", "S", "H", "L", "N"], false),
+    ("MSA", &["X", "S", "H", "L", "N"], false),
+    ("S", &["X", "N", "P"], false),
+    ("AU", &["X", "N", "Y"], false),
+    ("R", &["X", "A", "U", "I"], false),
+    ("V", &["X", "D", "C"], false),
+    ("RE", &["X", "L", "M", "H"], false),
+    ("U", &["X", "Clear", "Green", "Amber", "Red"], false),
+];
+
+impl CvssVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CvssVersion::V2 => "2.0",
+            CvssVersion::V3_0 => "3.0",
+            CvssVersion::V3_1 => "3.1",
+            CvssVersion::V4_0 => "4.0",
+        }
+    }
+
+    fn metric_defs(&self) -> &'static [MetricDef] {
+        match self {
+            CvssVersion::V2 => &V2_METRICS,
+            CvssVersion::V3_0 | CvssVersion::V3_1 => &V3_METRICS,
+            CvssVersion::V4_0 => &V4_METRICS,
+        }
+    }
+}
+
+impl Cvss {
+    /// Parses `CVSS:3.1/AV:N/...` and `CVSS:4.0/AV:N/...` vectors, or prefix-less
+    /// (optionally parenthesized) v2 vectors like `AV:N/AC:L/Au:N/C:P/I:P/A:P`.
+    pub fn parse(vector: &str) -> Result<Cvss, String> {
+        let vector = vector.trim();
+        let (version, body) = match vector.strip_prefix("CVSS:") {
+            Some(rest) => {
+                let (version, body) = rest
+                    .split_once('/')
+                    .ok_or_else(|| format!("invalid cvss vector: {}", vector))?;
+                let version = match version {
+                    "2.0" => CvssVersion::V2,
+                    "3.0" => CvssVersion::V3_0,
+                    "3.1" => CvssVersion::V3_1,
+                    "4.0" => CvssVersion::V4_0,
+                    _ => return Err(format!("unsupported cvss version: {}", vector)),
+                };
+                (version, body)
+            }
+            None => (
+                CvssVersion::V2,
+                vector.trim_start_matches('(').trim_end_matches(')'),
+            ),
+        };
+
+        let defs = version.metric_defs();
+        let mut metrics: Vec<(String, String)> = Vec::new();
+        for part in body.split('/') {
+            let (key, value) = part
+                .split_once(':')
+                .ok_or_else(|| format!("invalid cvss metric {:?} in {}", part, vector))?;
+            let def = defs
+                .iter()
+                .find(|x| x.0 == key)
+                .ok_or_else(|| format!("unknown cvss metric {:?} in {}", key, vector))?;
+            if !def.1.contains(&value) {
+                return Err(format!("invalid value {:?} of cvss metric {} in {}", value, key, vector));
+            }
+            if metrics.iter().any(|(k, _)| k == key) {
+                return Err(format!("duplicate cvss metric {} in {}", key, vector));
+            }
+            metrics.push((key.to_string(), value.to_string()));
+        }
+        for def in defs.iter().filter(|x| x.2) {
+            if !metrics.iter().any(|(k, _)| k == def.0) {
+                return Err(format!("missing cvss metric {} in {}", def.0, vector));
+            }
+        }
+        Ok(Cvss { version, metrics })
+    }
+
+    pub fn version(&self) -> CvssVersion {
+        self.version
+    }
+
+    /// The raw value of any metric of the vector, e.g. `metric("AV") == Some("N")`.
+    pub fn metric(&self, key: &str) -> Option<&str> {
+        self.metrics
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The value of a base metric, always present after `parse`.
+    fn base(&self, key: &str) -> &str {
+        self.metric(key).unwrap_or_default()
+    }
+
+    pub fn attack_vector(&self) -> AttackVector {
+        match self.base("AV") {
+            "N" => AttackVector::Network,
+            "A" => AttackVector::Adjacent,
+            "L" => AttackVector::Local,
+            _ => AttackVector::Physical,
+        }
+    }
+
+    pub fn attack_complexity(&self) -> AttackComplexity {
+        match self.base("AC") {
+            "L" => AttackComplexity::Low,
+            "M" => AttackComplexity::Medium,
+            _ => AttackComplexity::High,
+        }
+    }
+
+    /// `None` for CVSS v2, which rates authentication (`Au`) instead.
+    pub fn privileges_required(&self) -> Option<PrivilegesRequired> {
+        match self.metric("PR")? {
+            "N" => Some(PrivilegesRequired::None),
+            "L" => Some(PrivilegesRequired::Low),
+            _ => Some(PrivilegesRequired::High),
+        }
+    }
+
+    /// `None` for CVSS v2.
+    pub fn user_interaction(&self) -> Option<UserInteraction> {
+        match self.metric("UI")? {
+            "N" => Some(UserInteraction::None),
+            "R" => Some(UserInteraction::Required),
+            "P" => Some(UserInteraction::Passive),
+            _ => Some(UserInteraction::Active),
+        }
+    }
+
+    /// Whether a v3 vulnerability reaches beyond its security scope.
+    pub fn scope_changed(&self) -> bool {
+        self.metric("S") == Some("C") && self.version != CvssVersion::V4_0
+    }
+
+    pub fn confidentiality(&self) -> Impact {
+        self.impact("C", "VC")
+    }
+
+    pub fn integrity(&self) -> Impact {
+        self.impact("I", "VI")
+    }
+
+    pub fn availability(&self) -> Impact {
+        self.impact("A", "VA")
+    }
+
+    fn impact(&self, key: &str, v4_key: &str) -> Impact {
+        let value = match self.version {
+            CvssVersion::V4_0 => self.base(v4_key),
+            _ => self.base(key),
+        };
+        match value {
+            "H" | "C" => Impact::High,
+            "L" | "P" => Impact::Low,
+            _ => Impact::None,
+        }
+    }
+
+    pub fn base_score(&self) -> f64 {
+        match self.version {
+            CvssVersion::V2 => self.score_v2(),
+            CvssVersion::V3_0 | CvssVersion::V3_1 => self.score_v3(),
+            CvssVersion::V4_0 => self.score_v4(),
+        }
+    }
+
+    /// The qualitative rating of the base score, CVSS v2 has no `Critical`.
+    pub fn severity(&self) -> Severity {
+        let score = self.base_score();
+        match self.version {
+            CvssVersion::V2 if score >= 7.0 => Severity::High,
+            CvssVersion::V2 if score == 0.0 => Severity::Low,
+            _ => Severity::from_score(score),
+        }
+    }
+
+    fn score_v2(&self) -> f64 {
+        let av = match self.base("AV") {
+            "L" => 0.395,
+            "A" => 0.646,
+            _ => 1.0,
+        };
+        let ac = match self.base("AC") {
+            "H" => 0.35,
+            "M" => 0.61,
+            _ => 0.71,
+        };
+        let au = match self.base("Au") {
+            "M" => 0.45,
+            "S" => 0.56,
+            _ => 0.704,
+        };
+        let cia = |key: &str| match self.base(key) {
+            "P" => 0.275,
+            "C" => 0.660,
+            _ => 0.0,
+        };
+        let impact = 10.41 * (1.0 - (1.0 - cia("C")) * (1.0 - cia("I")) * (1.0 - cia("A")));
+        let exploitability = 20.0 * av * ac * au;
+        let f = if impact == 0.0 { 0.0 } else { 1.176 };
+        round_1((0.6 * impact + 0.4 * exploitability - 1.5) * f)
+    }
+
+    fn score_v3(&self) -> f64 {
+        let changed = self.scope_changed();
+        let av = match self.base("AV") {
+            "N" => 0.85,
+            "A" => 0.62,
+            "L" => 0.55,
+            _ => 0.2,
+        };
+        let ac = match self.base("AC") {
+            "L" => 0.77,
+            _ => 0.44,
+        };
+        let pr = match (self.base("PR"), changed) {
+            ("N", _) => 0.85,
+            ("L", false) => 0.62,
+            ("L", true) => 0.68,
+            (_, false) => 0.27,
+            (_, true) => 0.5,
+        };
+        let ui = match self.base("UI") {
+            "N" => 0.85,
+            _ => 0.62,
+        };
+        let cia = |key: &str| -> f64 {
+            match self.base(key) {
+                "H" => 0.56,
+                "L" => 0.22,
+                _ => 0.0,
+            }
+        };
+        let iss = 1.0 - (1.0 - cia("C")) * (1.0 - cia("I")) * (1.0 - cia("A"));
+        let impact = if changed {
+            7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15)
+        } else {
+            6.42 * iss
+        };
+        if impact <= 0.0 {
+            return 0.0;
+        }
+        let exploitability = 8.22 * av * ac * pr * ui;
+        let score = if changed {
+            (1.08 * (impact + exploitability)).min(10.0)
+        } else {
+            (impact + exploitability).min(10.0)
+        };
+        if self.version == CvssVersion::V3_0 {
+            (score * 10.0).ceil() / 10.0
+        } else {
+            round_up(score)
+        }
+    }
+
+    /// The CVSS-B score of the FIRST v4.0 calculator: the macro vector score, lowered
+    /// by the distance of the vector to the highest vector of its macro vector.
+    fn score_v4(&self) -> f64 {
+        // base score only: threat and environmental metrics keep their defaults
+        let m = |key: &str| match key {
+            "E" => "A",
+            "CR" | "IR" | "AR" => "H",
+            _ => self.base(key),
+        };
+        if ["VC", "VI", "VA", "SC", "SI", "SA"].iter().all(|x| m(x) == "N") {
+            return 0.0;
+        }
+
+        let eq1 = if m("AV") == "N" && m("PR") == "N" && m("UI") == "N" {
+            0
+        } else if (m("AV") == "N" || m("PR") == "N" || m("UI") == "N") && m("AV") != "P" {
+            1
+        } else {
+            2
+        };
+        let eq2 = if m("AC") == "L" && m("AT") == "N" { 0 } else { 1 };
+        let eq3 = if m("VC") == "H" && m("VI") == "H" {
+            0
+        } else if m("VC") == "H" || m("VI") == "H" || m("VA") == "H" {
+            1
+        } else {
+            2
+        };
+        let eq4 = if m("SI") == "S" || m("SA") == "S" {
+            0
+        } else if m("SC") == "H" || m("SI") == "H" || m("SA") == "H" {
+            1
+        } else {
+            2
+        };
+        let eq5 = match m("E") {
+            "A" => 0,
+            "P" => 1,
+            _ => 2,
+        };
+        let eq6 = if (m("CR") == "H" && m("VC") == "H")
+            || (m("IR") == "H" && m("VI") == "H")
+            || (m("AR") == "H" && m("VA") == "H")
+        {
+            0
+        } else {
+            1
+        };
+
+        let macro_vector = [eq1, eq2, eq3, eq4, eq5, eq6];
+        let value = match v4_lookup(macro_vector) {
+            Some(v) => v,
+            None => return 0.0,
+        };
+        let lower = |index: usize| {
+            let mut next = macro_vector;
+            next[index] += 1;
+            v4_lookup(next)
+        };
+        let lower_eq3_eq6 = match (eq3, eq6) {
+            (0, 0) => lower(5).zip(lower(2)).map(|(left, right)| left.max(right)),
+            (1, 0) => lower(5),
+            _ => lower(2),
+        };
+
+        // the distances to the first highest vector of the macro vector the vector does not exceed
+        let mut max_vectors = Vec::new();
+        for eq1_max in V4_MAX_EQ1[eq1] {
+            for eq2_max in V4_MAX_EQ2[eq2] {
+                for eq3_eq6_max in v4_max_eq3_eq6(eq3, eq6) {
+                    for eq4_max in V4_MAX_EQ4[eq4] {
+                        max_vectors.push([*eq1_max, *eq2_max, *eq3_eq6_max, *eq4_max].join("/"));
+                    }
+                }
+            }
+        }
+        let distance = |max_vector: &str, key: &str| {
+            let max = max_vector
+                .split('/')
+                .find_map(|x| x.strip_prefix(key).and_then(|x| x.strip_prefix(':')))
+                .unwrap_or_default();
+            v4_level(key, m(key)) - v4_level(key, max)
+        };
+        let keys = [
+            "AV", "PR", "UI", "AC", "AT", "VC", "VI", "VA", "SC", "SI", "SA", "CR", "IR", "AR",
+        ];
+        let distances = max_vectors
+            .iter()
+            .find(|x| keys.iter().all(|key| distance(x, key) >= 0.0))
+            .map(|x| {
+                let sum = |keys: &[&str]| keys.iter().map(|key| distance(x, key)).sum::<f64>();
+                [
+                    sum(&["AV", "PR", "UI"]),
+                    sum(&["AC", "AT"]),
+                    sum(&["VC", "VI", "VA", "CR", "IR", "AR"]),
+                    sum(&["SC", "SI", "SA"]),
+                ]
+            })
+            .unwrap_or_default();
+
+        let step = 0.1;
+        let max_severity = [
+            V4_MAX_SEVERITY_EQ1[eq1] * step,
+            V4_MAX_SEVERITY_EQ2[eq2] * step,
+            v4_max_severity_eq3_eq6(eq3, eq6) * step,
+            V4_MAX_SEVERITY_EQ4[eq4] * step,
+        ];
+        let lowers = [lower(0), lower(1), lower_eq3_eq6, lower(3)];
+        let mut existing = 0;
+        let mut normalized = 0.0;
+        for i in 0..4 {
+            if let Some(lower) = lowers[i] {
+                existing += 1;
+                normalized += (value - lower) * (distances[i] / max_severity[i]);
+            }
+        }
+        // the exploit maturity never lowers the base score, but still counts
+        if lower(4).is_some() {
+            existing += 1;
+        }
+        let mean = if existing == 0 { 0.0 } else { normalized / existing as f64 };
+        round_1((value - mean).clamp(0.0, 10.0))
+    }
+}
+
+impl fmt::Display for Cvss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|(k, v)| format!("{}:{}", k, v))
+            .collect::<Vec<String>>()
+            .join("/");
+        match self.version {
+            CvssVersion::V2 => write!(f, "{}", metrics),
+            _ => write!(f, "CVSS:{}/{}", self.version.as_str(), metrics),
+        }
+    }
+}
+
+/// The first number between 0 and 10 in scraped text like `9.8` or `CVSS 7.5 高危`.
+pub fn parse_score(text: &str) -> Option<f64> {
+    text.split(|x: char| !(x.is_ascii_digit() || x == '.'))
+        .filter_map(|x| x.parse::<f64>().ok())
+        .find(|x| (0.0..=10.0).contains(x))
+}
+
+fn round_1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// The CVSS v3.1 `Roundup`, which avoids floating point artifacts of a plain ceil.
+fn round_up(value: f64) -> f64 {
+    let int_input = (value * 100000.0).round() as i64;
+    if int_input % 10000 == 0 {
+        int_input as f64 / 100000.0
+    } else {
+        (int_input / 10000 + 1) as f64 / 10.0
+    }
+}
+
+fn v4_level(key: &str, value: &str) -> f64 {
+    match (key, value) {
+        ("AV", "A") | ("PR", "L") | ("UI", "P") | ("AC", "H") | ("AT", "P") => 0.1,
+        ("AV", "L") | ("PR", "H") | ("UI", "A") => 0.2,
+        ("AV", "P") => 0.3,
+        ("VC" | "VI" | "VA", "L") => 0.1,
+        ("VC" | "VI" | "VA", "N") => 0.2,
+        ("SC" | "SI" | "SA", "H") => 0.1,
+        ("SC" | "SI" | "SA", "L") => 0.2,
+        ("SC" | "SI" | "SA", "N") => 0.3,
+        ("CR" | "IR" | "AR", "M") => 0.1,
+        ("CR" | "IR" | "AR", "L") => 0.2,
+        _ => 0.0,
+    }
+}
+
+const V4_MAX_EQ1: [&[&str]; 3] = [
+    &["AV:N/PR:N/UI:N"],
+    &["AV:A/PR:N/UI:N", "AV:N/PR:L/UI:N", "AV:N/PR:N/UI:P"],
+    &["AV:P/PR:N/UI:N", "AV:A/PR:L/UI:P"],
+];
+
+const V4_MAX_EQ2: [&[&str]; 2] = [&["AC:L/AT:N"], &["AC:H/AT:N", "AC:L/AT:P"]];
+
+const V4_MAX_EQ4: [&[&str]; 3] = [&["SC:H/SI:S/SA:S"], &["SC:H/SI:H/SA:H"], &["SC:L/SI:L/SA:L"]];
+
+fn v4_max_eq3_eq6(eq3: usize, eq6: usize) -> &'static [&'static str] {
+    match (eq3, eq6) {
+        (0, 0) => &["VC:H/VI:H/VA:H/CR:H/IR:H/AR:H"],
+        (0, _) => &["VC:H/VI:H/VA:L/CR:M/IR:M/AR:H", "VC:H/VI:H/VA:H/CR:M/IR:M/AR:M"],
+        (1, 0) => &["VC:L/VI:H/VA:H/CR:H/IR:H/AR:H", "VC:H/VI:L/VA:H/CR:H/IR:H/AR:H"],
+        (1, _) => &[
+            "VC:L/VI:H/VA:L/CR:H/IR:M/AR:H",
+            "VC:L/VI:H/VA:H/CR:H/IR:M/AR:M",
+            "VC:H/VI:L/VA:H/CR:M/IR:H/AR:M",
+            "VC:H/VI:L/VA:L/CR:M/IR:H/AR:H",
+            "VC:L/VI:L/VA:H/CR:H/IR:H/AR:M",
+        ],
+        _ => &["VC:L/VI:L/VA:L/CR:H/IR:H/AR:H"],
+    }
+}
+
+const V4_MAX_SEVERITY_EQ1: [f64; 3] = [1.0, 4.0, 5.0];
+const V4_MAX_SEVERITY_EQ2: [f64; 2] = [1.0, 2.0];
+const V4_MAX_SEVERITY_EQ4: [f64; 3] = [6.0, 5.0, 4.0];
+
+fn v4_max_severity_eq3_eq6(eq3: usize, eq6: usize) -> f64 {
+    match (eq3, eq6) {
+        (0, 0) => 7.0,
+        (0, _) => 6.0,
+        (1, _) => 8.0,
+        _ => 10.0,
+    }
+}
+
+fn v4_lookup(macro_vector: [usize; 6]) -> Option<f64> {
+    let key: String = macro_vector.iter().map(|x| x.to_string()).collect();
+    V4_LOOKUP.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// The score of every CVSS v4.0 macro vector `eq1 eq2 eq3 eq4 eq5 eq6`.
+const V4_LOOKUP: [(&str, f64); 270] = [
+    ("000000", 10.0), ("000001", 9.9), ("000010", 9.8), ("000011", 9.5), ("000020", 9.5), ("000021", 9.2),
+    ("000100", 10.0), ("000101", 9.6), ("000110", 9.3), ("000111", 8.7), ("000120", 9.1), ("000121", 8.1),
+    ("000200", 9.3), ("000201", 9.0), ("000210", 8.9), ("000211", 8.0), ("000220", 8.1), ("000221", 6.8),
+    ("001000", 9.8), ("001001", 9.5), ("001010", 9.5), ("001011", 9.2), ("001020", 9.0), ("001021", 8.4),
+    ("001100", 9.3), ("001101", 9.2), ("001110", 8.9), ("001111", 8.1), ("001120", 8.1), ("001121", 6.5),
+    ("001200", 8.8), ("001201", 8.0), ("001210", 7.8), ("001211", 7.0), ("001220", 6.9), ("001221", 4.8),
+    ("002001", 9.2), ("002011", 8.2), ("002021", 7.2), ("002101", 7.9), ("002111", 6.9), ("002121", 5.0),
+    ("002201", 6.9), ("002211", 5.5), ("002221", 2.7),
+    ("010000", 9.9), ("010001", 9.7), ("010010", 9.5), ("010011", 9.2), ("010020", 9.2), ("010021", 8.5),
+    ("010100", 9.5), ("010101", 9.1), ("010110", 9.0), ("010111", 8.3), ("010120", 8.4), ("010121", 7.1),
+    ("010200", 9.2), ("010201", 8.1), ("010210", 8.2), ("010211", 7.1), ("010220", 7.2), ("010221", 5.3),
+    ("011000", 9.5), ("011001", 9.3), ("011010", 9.2), ("011011", 8.5), ("011020", 8.5), ("011021", 7.3),
+    ("011100", 9.2), ("011101", 8.2), ("011110", 8.0), ("011111", 7.2), ("011120", 7.0), ("011121", 5.9),
+    ("011200", 8.4), ("011201", 7.0), ("011210", 7.1), ("011211", 5.2), ("011220", 5.0), ("011221", 3.0),
+    ("012001", 8.6), ("012011", 7.5), ("012021", 5.2), ("012101", 7.1), ("012111", 5.2), ("012121", 2.9),
+    ("012201", 6.3), ("012211", 2.9), ("012221", 1.7),
+    ("100000", 9.8), ("100001", 9.5), ("100010", 9.4), ("100011", 8.7), ("100020", 9.1), ("100021", 8.1),
+    ("100100", 9.4), ("100101", 8.9), ("100110", 8.6), ("100111", 7.4), ("100120", 7.7), ("100121", 6.4),
+    ("100200", 8.7), ("100201", 7.5), ("100210", 7.4), ("100211", 6.3), ("100220", 6.3), ("100221", 4.9),
+    ("101000", 9.4), ("101001", 8.9), ("101010", 8.8), ("101011", 7.7), ("101020", 7.6), ("101021", 6.7),
+    ("101100", 8.6), ("101101", 7.6), ("101110", 7.4), ("101111", 5.8), ("101120", 5.9), ("101121", 5.0),
+    ("101200", 7.2), ("101201", 5.7), ("101210", 5.7), ("101211", 5.2), ("101220", 5.2), ("101221", 2.5),
+    ("102001", 8.3), ("102011", 7.0), ("102021", 5.4), ("102101", 6.5), ("102111", 5.8), ("102121", 2.6),
+    ("102201", 5.3), ("102211", 2.1), ("102221", 1.3),
+    ("110000", 9.5), ("110001", 9.0), ("110010", 8.8), ("110011", 7.6), ("110020", 7.6), ("110021", 7.0),
+    ("110100", 9.0), ("110101", 7.7), ("110110", 7.5), ("110111", 6.2), ("110120", 6.1), ("110121", 5.3),
+    ("110200", 7.7), ("110201", 6.6), ("110210", 6.8), ("110211", 5.9), ("110220", 5.2), ("110221", 3.0),
+    ("111000", 8.9), ("111001", 7.8), ("111010", 7.6), ("111011", 6.7), ("111020", 6.2), ("111021", 5.8),
+    ("111100", 7.4), ("111101", 5.9), ("111110", 5.7), ("111111", 5.7), ("111120", 4.7), ("111121", 2.3),
+    ("111200", 6.1), ("111201", 5.2), ("111210", 5.7), ("111211", 2.9), ("111220", 2.4), ("111221", 1.6),
+    ("112001", 7.1), ("112011", 5.9), ("112021", 3.0), ("112101", 5.8), ("112111", 2.6), ("112121", 1.5),
+    ("112201", 2.3), ("112211", 1.3), ("112221", 0.6),
+    ("200000", 9.3), ("200001", 8.7), ("200010", 8.6), ("200011", 7.2), ("200020", 7.5), ("200021", 5.8),
+    ("200100", 8.6), ("200101", 7.4), ("200110", 7.4), ("200111", 6.1), ("200120", 5.6), ("200121", 3.4),
+    ("200200", 7.0), ("200201", 5.4), ("200210", 5.2), ("200211", 4.0), ("200220", 4.0), ("200221", 2.2),
+    ("201000", 8.5), ("201001", 7.5), ("201010", 7.4), ("201011", 5.5), ("201020", 6.2), ("201021", 5.1),
+    ("201100", 7.2), ("201101", 5.7), ("201110", 5.5), ("201111", 4.1), ("201120", 4.6), ("201121", 1.9),
+    ("201200", 5.3), ("201201", 3.6), ("201210", 3.4), ("201211", 1.9), ("201220", 1.9), ("201221", 0.8),
+    ("202001", 6.4), ("202011", 5.1), ("202021", 2.0), ("202101", 4.7), ("202111", 2.1), ("202121", 1.1),
+    ("202201", 2.4), ("202211", 0.9), ("202221", 0.4),
+    ("210000", 8.8), ("210001", 7.5), ("210010", 7.3), ("210011", 5.3), ("210020", 6.0), ("210021", 5.0),
+    ("210100", 7.3), ("210101", 5.5), ("210110", 5.9), ("210111", 4.0), ("210120", 4.1), ("210121", 2.0),
+    ("210200", 5.4), ("210201", 4.3), ("210210", 4.5), ("210211", 2.2), ("210220", 2.0), ("210221", 1.1),
+    ("211000", 7.5), ("211001", 5.5), ("211010", 5.8), ("211011", 4.5), ("211020", 4.0), ("211021", 2.1),
+    ("211100", 6.1), ("211101", 5.1), ("211110", 4.8), ("211111", 1.8), ("211120", 2.0), ("211121", 0.9),
+    ("211200", 4.6), ("211201", 1.8), ("211210", 1.7), ("211211", 0.7), ("211220", 0.8), ("211221", 0.2),
+    ("212001", 5.3), ("212011", 2.4), ("212021", 1.4), ("212101", 2.4), ("212111", 1.2), ("212121", 0.5),
+    ("212201", 1.0), ("212211", 0.3), ("212221", 0.1),
+];
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn score(vector: &str) -> f64 {
+        Cvss::parse(vector).unwrap().base_score()
+    }
+
+    #[test]
+    fn test_parse() {
+        let cvss = Cvss::parse("CVSS:3.1/AV:N/AC:L/PR:H/UI:N/S:U/C:H/I:H/A:H/E:P").unwrap();
+        assert_eq!(cvss.version(), CvssVersion::V3_1);
+        assert_eq!(cvss.attack_vector(), AttackVector::Network);
+        assert_eq!(cvss.privileges_required(), Some(PrivilegesRequired::High));
+        assert_eq!(cvss.user_interaction(), Some(UserInteraction::None));
+        assert_eq!(cvss.metric("E"), Some("P"));
+        assert_eq!(cvss.to_string(), "CVSS:3.1/AV:N/AC:L/PR:H/UI:N/S:U/C:H/I:H/A:H/E:P");
+
+        let cvss = Cvss::parse("(AV:N/AC:M/Au:N/C:P/I:N/A:C)").unwrap();
+        assert_eq!(cvss.version(), CvssVersion::V2);
+        assert_eq!(cvss.attack_complexity(), AttackComplexity::Medium);
+        assert_eq!(cvss.privileges_required(), None);
+        assert_eq!(cvss.confidentiality(), Impact::Low);
+        assert_eq!(cvss.availability(), Impact::High);
+
+        assert!(Cvss::parse("CVSS:3.1/AV:N/AC:L/PR:H/UI:N/S:U/C:H/I:H").is_err());
+        assert!(Cvss::parse("CVSS:3.1/AV:X/AC:L/PR:H/UI:N/S:U/C:H/I:H/A:H").is_err());
+        assert!(Cvss::parse("CVSS:3.1/AV:N/AV:N/AC:L/PR:H/UI:N/S:U/C:H/I:H/A:H").is_err());
+        assert!(Cvss::parse("CVSS:5.0/AV:N").is_err());
+        assert!(Cvss::parse("").is_err());
+    }
+
+    #[test]
+    fn test_score_v2() {
+        assert_eq!(score("AV:N/AC:L/Au:N/C:P/I:P/A:P"), 7.5);
+        assert_eq!(score("AV:N/AC:L/Au:S/C:C/I:C/A:C"), 9.0);
+        assert_eq!(score("AV:N/AC:L/Au:N/C:N/I:N/A:N"), 0.0);
+        assert_eq!(Cvss::parse("AV:N/AC:L/Au:N/C:C/I:C/A:C").unwrap().severity(), Severity::High);
+    }
+
+    #[test]
+    fn test_score_v3() {
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), 9.8);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:H/UI:N/S:U/C:H/I:H/A:H"), 7.2);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:U/C:H/I:H/A:H"), 8.8);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"), 10.0);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"), 6.1);
+        assert_eq!(score("CVSS:3.0/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:N/A:N"), 5.5);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"), 0.0);
+        let cvss = Cvss::parse("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H").unwrap();
+        assert_eq!(cvss.severity(), Severity::Critical);
+    }
+
+    #[test]
+    fn test_score_v4() {
+        assert_eq!(score("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N"), 9.3);
+        assert_eq!(score("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:H/SI:H/SA:H"), 10.0);
+        assert_eq!(score("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:N/VI:N/VA:H/SC:N/SI:N/SA:N"), 8.7);
+        assert_eq!(score("CVSS:4.0/AV:L/AC:L/AT:N/PR:L/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N"), 8.5);
+        assert_eq!(score("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:N/VI:N/VA:N/SC:N/SI:N/SA:N"), 0.0);
+        let cvss = Cvss::parse("CVSS:4.0/AV:N/AC:L/AT:P/PR:N/UI:A/VC:H/VI:N/VA:N/SC:N/SI:N/SA:N").unwrap();
+        assert_eq!(cvss.user_interaction(), Some(UserInteraction::Active));
+        assert_eq!(cvss.integrity(), Impact::None);
+        assert!(!cvss.scope_changed());
+    }
+
+    #[test]
+    fn test_parse_score() {
+        assert_eq!(parse_score("9.8"), Some(9.8));
+        assert_eq!(parse_score(" CVSS 7.5 高危"), Some(7.5));
+        assert_eq!(parse_score("暂无"), None);
+    }
+}
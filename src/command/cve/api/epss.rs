@@ -0,0 +1,140 @@
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+
+/// Exploit prediction scores of a FIRST EPSS daily snapshot
+/// (`epss_scores-YYYY-MM-DD.csv`, optionally gzipped).
+pub struct Epss {
+    scores: HashMap<String, EpssScore>,
+    model_version: String,
+    score_date: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EpssScore {
+    /// Probability of exploitation activity in the next 30 days.
+    pub epss: f64,
+    pub percentile: f64,
+}
+
+impl Epss {
+    pub fn new() -> Epss {
+        Epss {
+            scores: HashMap::new(),
+            model_version: String::new(),
+            score_date: String::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Epss, Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+        let mut data = String::new();
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut data)?;
+        } else {
+            data = String::from_utf8(bytes)?;
+        }
+        Ok(Epss::parser_csv(&data))
+    }
+
+    /// Reads the `#model_version:...,score_date:...` comment, the `cve,epss,percentile`
+    /// header and one line per CVE, skipping lines that do not parse.
+    fn parser_csv(data: &str) -> Epss {
+        let mut epss = Epss::new();
+        for line in data.lines().map(|x| x.trim()) {
+            if let Some(comment) = line.strip_prefix('#') {
+                for item in comment.split(',') {
+                    match item.split_once(':') {
+                        Some(("model_version", v)) => epss.model_version = v.to_string(),
+                        Some(("score_date", v)) => epss.score_date = v.to_string(),
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+            let cols: Vec<&str> = line.split(',').collect();
+            if cols.len() < 3 || !cols[0].starts_with("CVE-") {
+                continue;
+            }
+            if let (Ok(score), Ok(percentile)) = (cols[1].parse::<f64>(), cols[2].parse::<f64>()) {
+                epss.scores.insert(
+                    cols[0].to_string(),
+                    EpssScore {
+                        epss: score,
+                        percentile,
+                    },
+                );
+            }
+        }
+        epss
+    }
+
+    pub fn get(&self, id: &str) -> Option<EpssScore> {
+        self.scores.get(id).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn stats(&self) -> String {
+        format!(
+            "epss: {} cves model: {} date: {}",
+            self.len(),
+            self.model_version,
+            self.score_date
+        )
+    }
+}
+
+impl Default for Epss {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const EPSS_CSV_STR: &str = "#model_version:v2023.03.01,score_date:2024-05-20T00:00:00+0000
+cve,epss,percentile
+CVE-2021-44228,0.97565,0.99995
+CVE-2023-25194,0.91906,0.98883
+CVE-2023-0001,n/a,0.1
+";
+
+    #[test]
+    fn test_load_gz() {
+        let dir = std::env::temp_dir().join("etool-epss-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("epss_scores-2024-05-20.csv.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(EPSS_CSV_STR.as_bytes()).unwrap();
+        fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let epss = Epss::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(epss.len(), 2);
+        assert_eq!(
+            epss.get("CVE-2021-44228"),
+            Some(EpssScore {
+                epss: 0.97565,
+                percentile: 0.99995
+            })
+        );
+        assert_eq!(epss.get("CVE-2023-0001"), None);
+        assert_eq!(epss.model_version, "v2023.03.01");
+        assert_eq!(epss.score_date, "2024-05-20T00:00:00+0000");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
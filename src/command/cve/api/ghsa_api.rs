@@ -0,0 +1,163 @@
+use crate::command::cve::api::lib::CveApi;
+use crate::command::cve::api::osv_api::{find_files, osv_cve_record, OsvPackageIndex, OsvRecord};
+use crate::command::cve::api::record::CveRecord;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const GHSA_CVE_API: &str = "GhsaApi";
+
+/// Answers queries from a local clone of github/advisory-database,
+/// resolving both GHSA and CVE identifiers.
+pub struct GhsaApi {
+    advisories: HashMap<String, OsvRecord>,
+    aliases: HashMap<String, Vec<String>>,
+    packages: OsvPackageIndex,
+}
+
+impl GhsaApi {
+    pub fn new() -> GhsaApi {
+        GhsaApi {
+            advisories: HashMap::new(),
+            aliases: HashMap::new(),
+            packages: OsvPackageIndex::default(),
+        }
+    }
+
+    /// `path` may point at the clone root, its `advisories` directory or `github-reviewed`.
+    pub fn load(path: &str) -> GhsaApi {
+        let mut ghsa_api = GhsaApi::new();
+        let mut root = PathBuf::from(path);
+        if root.join("advisories").is_dir() {
+            root = root.join("advisories");
+        }
+        if root.join("github-reviewed").is_dir() {
+            root = root.join("github-reviewed");
+        }
+
+        let mut files = Vec::new();
+        find_files(&root, ".json", &mut files);
+        println!("load ghsa advisories: {:?} ({} files)", root, files.len());
+        for file in files.iter() {
+            let is_advisory = file
+                .file_name()
+                .map(|x| x.to_string_lossy().starts_with("GHSA-"))
+                .unwrap_or(false);
+            if is_advisory {
+                ghsa_api.read_advisory(file);
+            }
+        }
+        ghsa_api
+    }
+
+    fn read_advisory(&mut self, path: &Path) {
+        let record = match fs::read_to_string(path) {
+            Ok(data) => OsvRecord::parse(&data),
+            Err(e) => Err(e.into()),
+        };
+        match record {
+            Ok(record) => self.insert(record),
+            Err(e) => {
+                println!("read ghsa advisory {:?} error: {:#?}", path, e);
+            }
+        }
+    }
+
+    fn insert(&mut self, record: OsvRecord) {
+        self.packages.insert(&record);
+        for id in record.cve_ids() {
+            let ids = self.aliases.entry(id).or_default();
+            if !ids.contains(&record.id) {
+                ids.push(record.id.clone());
+            }
+        }
+        self.advisories.insert(record.id.clone(), record);
+    }
+
+    pub fn len(&self) -> usize {
+        self.advisories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+}
+
+impl Default for GhsaApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CveApi for GhsaApi {
+    fn query(&self, id: &str) -> CveRecord {
+        let records: Vec<OsvRecord> = if id.starts_with("GHSA-") {
+            self.advisories.get(id).into_iter().cloned().collect()
+        } else {
+            self.aliases
+                .get(id)
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|x| self.advisories.get(x).cloned())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        osv_cve_record(id, &records)
+    }
+
+    fn id(&self) -> String {
+        String::from(GHSA_CVE_API)
+    }
+
+    fn query_package(&self, purl: &str, version: &str) -> Vec<String> {
+        self.packages.query(purl, version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::command::cve::api::record::Severity;
+
+    const GHSA_ADVISORY_STR: &str = r#"{
+        "schema_version": "1.4.0",
+        "id": "GHSA-jfh8-c2jp-5v3q",
+        "published": "2021-12-10T00:40:56Z",
+        "aliases": ["CVE-2021-44228"],
+        "summary": "Remote code injection in Log4j",
+        "details": "Logging untrusted data with log4j versions 2.14.1 and below may result in RCE.",
+        "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"}],
+        "affected": [{
+            "package": {"ecosystem": "Maven", "name": "org.apache.logging.log4j:log4j-core"},
+            "ranges": [{"type": "ECOSYSTEM", "events": [{"introduced": "2.13.0"}, {"fixed": "2.15.0"}]}]
+        }],
+        "references": [{"type": "ADVISORY", "url": "https://nvd.nist.gov/vuln/detail/CVE-2021-44228"}],
+        "database_specific": {"cwe_ids": ["CWE-20", "CWE-400", "CWE-502"], "severity": "CRITICAL", "github_reviewed": true}
+    }"#;
+
+    #[test]
+    fn test_query() {
+        let root = std::env::temp_dir().join("etool-ghsa-test");
+        let dir = root.join("advisories/github-reviewed/2021/12/GHSA-jfh8-c2jp-5v3q");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("GHSA-jfh8-c2jp-5v3q.json"), GHSA_ADVISORY_STR).unwrap();
+
+        let ghsa_api = GhsaApi::load(root.to_str().unwrap());
+        assert_eq!(ghsa_api.len(), 1);
+        let cve = ghsa_api.query("CVE-2021-44228");
+        assert_eq!(cve.aliases, vec!["GHSA-jfh8-c2jp-5v3q"]);
+        assert_eq!(cve.severity, Some(Severity::Critical));
+        assert_eq!(cve.cvss[0].version, "3.1");
+        assert_eq!(cve.score(), Some(10.0));
+        assert_eq!(cve.cwe, vec!["CWE-20", "CWE-400", "CWE-502"]);
+        assert_eq!(cve.fixed, vec!["Maven/org.apache.logging.log4j:log4j-core 2.15.0"]);
+        assert_eq!(
+            ghsa_api.query("GHSA-jfh8-c2jp-5v3q").title,
+            "Remote code injection in Log4j"
+        );
+        assert!(ghsa_api.query("CVE-2000-0001").is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
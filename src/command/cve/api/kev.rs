@@ -0,0 +1,140 @@
+use serde::{self, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+/// The CISA Known Exploited Vulnerabilities catalog (`known_exploited_vulnerabilities.json`).
+pub struct Kev {
+    entries: HashMap<String, KevEntry>,
+    catalog_version: String,
+    date_released: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KevEntry {
+    #[serde(rename = "cveID")]
+    pub cve_id: String,
+    #[serde(default)]
+    pub vendor_project: String,
+    #[serde(default)]
+    pub product: String,
+    #[serde(default)]
+    pub date_added: String,
+    #[serde(default)]
+    pub required_action: String,
+    #[serde(default)]
+    pub due_date: String,
+    /// `Known` or `Unknown`.
+    #[serde(default)]
+    pub known_ransomware_campaign_use: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KevCatalog {
+    #[serde(default)]
+    catalog_version: String,
+    #[serde(default)]
+    date_released: String,
+    vulnerabilities: Vec<KevEntry>,
+}
+
+impl Kev {
+    pub fn new() -> Kev {
+        Kev {
+            entries: HashMap::new(),
+            catalog_version: String::new(),
+            date_released: String::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Kev, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(Kev::parser_json(&contents)?)
+    }
+
+    fn parser_json(contents: &str) -> Result<Kev, serde_json::Error> {
+        let catalog: KevCatalog = serde_json::from_str(contents)?;
+        let mut kev = Kev::new();
+        kev.catalog_version = catalog.catalog_version;
+        kev.date_released = catalog.date_released;
+        for entry in catalog.vulnerabilities {
+            kev.entries.insert(entry.cve_id.clone(), entry);
+        }
+        Ok(kev)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&KevEntry> {
+        self.entries.get(id)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> String {
+        format!(
+            "kev: {} cves version: {} released: {}",
+            self.len(),
+            self.catalog_version,
+            self.date_released
+        )
+    }
+}
+
+impl Default for Kev {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const KEV_JSON_STR: &str = r#"{
+    "title": "CISA Catalog of Known Exploited Vulnerabilities",
+    "catalogVersion": "2024.05.20",
+    "dateReleased": "2024-05-20T15:59:06.6431Z",
+    "count": 1,
+    "vulnerabilities": [
+        {
+            "cveID": "CVE-2021-44228",
+            "vendorProject": "Apache",
+            "product": "Log4j2",
+            "vulnerabilityName": "Apache Log4j2 Remote Code Execution Vulnerability",
+            "dateAdded": "2021-12-10",
+            "shortDescription": "Apache Log4j2 contains a vulnerability where JNDI features do not protect against attacker-controlled JNDI-related endpoints, allowing for remote code execution.",
+            "requiredAction": "For all affected software assets for which updates exist, the only acceptable remediation actions are: 1) Apply updates; OR 2) remove affected assets from agency networks.",
+            "dueDate": "2021-12-24",
+            "knownRansomwareCampaignUse": "Known",
+            "notes": "https://nvd.nist.gov/vuln/detail/CVE-2021-44228",
+            "cwes": ["CWE-20", "CWE-400", "CWE-502"]
+        }
+    ]
+}"#;
+
+    #[test]
+    fn test_parser_json() {
+        let kev = Kev::parser_json(KEV_JSON_STR).unwrap();
+        assert_eq!(kev.len(), 1);
+        assert!(kev.contains("CVE-2021-44228"));
+        assert!(!kev.contains("CVE-2023-25194"));
+        let entry = kev.get("CVE-2021-44228").unwrap();
+        assert_eq!(entry.date_added, "2021-12-10");
+        assert_eq!(entry.due_date, "2021-12-24");
+        assert_eq!(entry.known_ransomware_campaign_use, "Known");
+        assert!(entry.required_action.starts_with("For all affected"));
+        assert_eq!(kev.catalog_version, "2024.05.20");
+    }
+}
@@ -1,42 +1,56 @@
-use std::{
-    collections::HashMap,
-};
-use std::future::Future;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use serde::Serialize;
+use crate::command::cve::api::cache::CveCache;
+pub use crate::command::cve::api::record::{CveRecord, CVE_FIELDS};
 
 pub trait CveApi: Sync + Send {
-    fn query(&self, cve_id: &str) -> Box<dyn Cve>;
+    /// The record of `cve_id`, empty when the provider does not know it.
+    fn query(&self, cve_id: &str) -> CveRecord;
     fn id(&self) -> String;
-}
 
-pub trait Cve {
-    fn to_json(&self) -> String;
-    fn get(&self, key: &str) -> String;
+    /// Whether answers come over the network and are worth caching.
+    fn remote(&self) -> bool {
+        false
+    }
+
+    /// The vulnerability ids affecting `version` of the package url `purl`, empty for
+    /// providers without package data.
+    fn query_package(&self, _purl: &str, _version: &str) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub struct CveApis {
-    apis: HashMap<String, Box<dyn CveApi>>
+    apis: HashMap<String, Arc<dyn CveApi>>,
+    chain: Vec<String>,
+    cache: Option<CveCache>,
+    offline: bool,
 }
 
 impl CveApis {
     pub fn new() -> CveApis {
-        let apis: HashMap<String, Box<dyn CveApi>> = HashMap::new();
+        let apis: HashMap<String, Arc<dyn CveApi>> = HashMap::new();
         CveApis {
             apis,
+            chain: Vec::new(),
+            cache: None,
+            offline: false,
         }
     }
 
     pub fn register(&mut self, cve_api: Box<dyn CveApi>) {
         let key = cve_api.id();
 
-        Ok((&mut self.apis, key, cve_api))
+        Ok((&mut self.apis, key, Arc::from(cve_api)))
             .and_then(|(x, y, z)| CveApis::insert(x, y, z))
             .unwrap();
     }
 
     fn insert(
-        x: &mut HashMap<String, Box<dyn CveApi>>,
+        x: &mut HashMap<String, Arc<dyn CveApi>>,
         key: String,
-        cve_api: Box<dyn CveApi>,
+        cve_api: Arc<dyn CveApi>,
     ) -> Result<(), ()> {
         // if !x.contains_key(&key) {
         //     x.insert(key, cve_api);
@@ -45,8 +59,106 @@ impl CveApis {
         Ok(())
     }
 
-    pub fn invoke(&self, key: &str, id: &str) -> Box<dyn Cve> {
-        self.apis.get(key).unwrap().query(id)
+    pub fn contains(&self, key: &str) -> bool {
+        self.apis.contains_key(key)
+    }
+
+    /// The provider registered as `key`, to query it without holding a lock on the registry.
+    pub fn provider(&self, key: &str) -> Option<Arc<dyn CveApi>> {
+        self.apis.get(key).cloned()
+    }
+
+    /// Sets the providers `invoke_chain` asks, in order.
+    pub fn set_chain(&mut self, chain: Vec<String>) {
+        self.chain = chain;
+    }
+
+    pub fn chain(&self) -> Vec<String> {
+        self.chain.clone()
+    }
+
+    pub fn set_cache(&mut self, cache: CveCache) {
+        self.cache = Some(cache);
+    }
+
+    /// Answers remote providers from the cache only.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn is_remote(&self, key: &str) -> bool {
+        self.apis.get(key).map(|x| x.remote()).unwrap_or(false)
+    }
+
+    /// The cached answer of a remote provider, `None` for local providers or a cache miss.
+    pub fn cache_get(&self, key: &str, id: &str) -> Option<CveRecord> {
+        match &self.cache {
+            Some(cache) if self.is_remote(key) => cache.get(key, id),
+            _ => None,
+        }
+    }
+
+    pub fn cache_put(&self, key: &str, id: &str, cve: &CveRecord) {
+        if let Some(cache) = &self.cache {
+            if self.is_remote(key) {
+                cache.put(key, id, cve);
+            }
+        }
+    }
+
+    pub fn cache_stats(&self) -> Option<String> {
+        self.cache.as_ref().map(|x| x.stats())
+    }
+
+    pub fn invoke(&self, key: &str, id: &str) -> Option<CveRecord> {
+        if let Some(cve) = self.cache_get(key, id) {
+            return Some(cve);
+        }
+        if self.offline && self.is_remote(key) {
+            return None;
+        }
+        let cve = self.apis.get(key).map(|x| x.query(id));
+        if let Some(cve) = &cve {
+            self.cache_put(key, id, cve);
+        }
+        cve
+    }
+
+    /// The vulnerability ids every registered provider reports for a package version.
+    pub fn query_package(&self, purl: &str, version: &str) -> Vec<String> {
+        let mut keys: Vec<&String> = self.apis.keys().collect();
+        keys.sort();
+        let mut ids: Vec<String> = Vec::new();
+        for key in keys {
+            for id in self.apis[key].query_package(purl, version) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+
+    /// Asks every provider of the chain in order, taking each field from the first provider that has it.
+    pub fn invoke_chain(&self, id: &str) -> MergedCve {
+        let mut merged = MergedCve::new();
+        for key in self.chain.iter() {
+            if merged.is_complete() {
+                break;
+            }
+            if !self.contains(key) {
+                println!("cve provider not registered {}", key);
+                continue;
+            }
+            if let Some(cve) = self.invoke(key, id) {
+                merged.merge(key, &cve);
+            }
+        }
+        merged
     }
 }
 
@@ -56,11 +168,148 @@ impl Default for CveApis {
     }
 }
 
+/// The result of a provider chain, remembering which provider answered each field.
+#[derive(Debug, Default, Serialize)]
+pub struct MergedCve {
+    record: CveRecord,
+    sources: BTreeMap<String, String>,
+}
+
+impl MergedCve {
+    pub fn new() -> MergedCve {
+        MergedCve {
+            record: CveRecord::new(),
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Takes every field still missing from `cve`, recording `source` as its origin.
+    pub fn merge(&mut self, source: &str, cve: &CveRecord) {
+        for key in self.record.merge(cve) {
+            self.sources.insert(key.to_string(), source.to_string());
+        }
+    }
+
+    pub fn record(&self) -> &CveRecord {
+        &self.record
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.sources.len() == CVE_FIELDS.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.record.is_empty()
+    }
+
+    pub fn source(&self, key: &str) -> String {
+        self.sources.get(key).cloned().unwrap_or_default()
+    }
+
+    /// The fields grouped by provider, e.g. `NvdApi: title, cvss`, one provider per line.
+    pub fn sources(&self) -> String {
+        let mut providers: Vec<(String, Vec<String>)> = Vec::new();
+        for key in CVE_FIELDS.iter() {
+            if let Some(source) = self.sources.get(*key) {
+                match providers.iter_mut().find(|(x, _)| x == source) {
+                    Some((_, keys)) => keys.push(key.to_string()),
+                    None => providers.push((source.clone(), vec![key.to_string()])),
+                }
+            }
+        }
+        providers
+            .iter()
+            .map(|(source, keys)| format!("{}: {}", source, keys.join(", ")))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
-    // use super::*;
+    use super::*;
+    use crate::command::cve::api::record::CvssMetric;
+
+    struct MockApi(&'static str, CveRecord);
+
+    impl CveApi for MockApi {
+        fn query(&self, _cve_id: &str) -> CveRecord {
+            self.1.clone()
+        }
+
+        fn id(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    fn record(title: &str, fix_label: &str, score: Option<f64>) -> CveRecord {
+        let mut record = CveRecord::new();
+        record.title = title.to_string();
+        record.fix_label = fix_label.to_string();
+        record.cvss = score
+            .map(|x| vec![CvssMetric::new("3.1", "", Some(x))])
+            .unwrap_or_default();
+        record
+    }
+
+    struct RemoteApi;
+
+    impl CveApi for RemoteApi {
+        fn query(&self, _cve_id: &str) -> CveRecord {
+            panic!("remote provider queried in offline mode")
+        }
+
+        fn id(&self) -> String {
+            String::from("RemoteApi")
+        }
+
+        fn remote(&self) -> bool {
+            true
+        }
+    }
 
     #[test]
     fn test_add() {}
+
+    #[test]
+    fn test_invoke_chain() {
+        let mut cve_apis = CveApis::new();
+        cve_apis.register(Box::new(MockApi("NvdApi", record("nvd title", " ", Some(7.5)))));
+        cve_apis.register(Box::new(MockApi("AliyunApi", record("aliyun title", "官方补丁", None))));
+        cve_apis.set_chain(vec![
+            String::from("NvdApi"),
+            String::from("MissingApi"),
+            String::from("AliyunApi"),
+        ]);
+
+        let cve = cve_apis.invoke_chain("CVE-2023-25194");
+        assert_eq!(cve.record().title, "nvd title");
+        assert_eq!(cve.record().fix_label, "官方补丁");
+        assert_eq!(cve.record().score(), Some(7.5));
+        assert_eq!(cve.source("cvss"), "NvdApi");
+        assert_eq!(cve.source("fix_label"), "AliyunApi");
+        assert_eq!(cve.sources(), "NvdApi: title, cvss\nAliyunApi: fix_label");
+        assert!(cve_apis.invoke("MissingApi", "CVE-2023-25194").is_none());
+        assert_eq!(cve_apis.provider("AliyunApi").unwrap().query("CVE-2023-25194").title, "aliyun title");
+        assert!(cve_apis.provider("MissingApi").is_none());
+    }
+
+    #[test]
+    fn test_invoke_chain_offline() {
+        let mut cve_apis = CveApis::new();
+        cve_apis.register(Box::new(MockApi("NvdApi", record("nvd title", "", None))));
+        cve_apis.register(Box::new(RemoteApi));
+        cve_apis.set_chain(vec![String::from("NvdApi"), String::from("RemoteApi")]);
+        cve_apis.set_offline(true);
+
+        let cve = cve_apis.invoke_chain("CVE-2023-25194");
+        assert_eq!(cve.record().title, "nvd title");
+        assert_eq!(cve.record().score(), None);
+        assert!(cve_apis.invoke("RemoteApi", "CVE-2023-25194").is_none());
+    }
 }
@@ -1,3 +1,14 @@
 pub mod aliyun_api;
+pub mod aliyun_search;
 pub mod base;
-pub mod lib;
\ No newline at end of file
+pub mod cache;
+pub mod cvelist_api;
+pub mod cvss;
+pub mod epss;
+pub mod ghsa_api;
+pub mod kev;
+pub mod lib;
+pub mod nvd_api;
+pub mod osv_api;
+pub mod record;
+pub mod version;
\ No newline at end of file
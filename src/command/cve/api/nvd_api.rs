@@ -0,0 +1,321 @@
+use crate::command::cve::api::lib::CveApi;
+use crate::command::cve::api::record::{parse_date, CveRecord, CvssMetric, Severity};
+use flate2::read::GzDecoder;
+use serde::{self, Deserialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+pub const NVD_CVE_API: &str = "NvdApi";
+
+/// Answers queries from locally downloaded NVD JSON 2.0 feeds
+/// (`nvdcve-2.0-*.json`, optionally gzipped).
+pub struct NvdApi {
+    cves: HashMap<String, CveRecord>,
+}
+
+impl NvdApi {
+    pub fn new() -> NvdApi {
+        NvdApi {
+            cves: HashMap::new(),
+        }
+    }
+
+    /// Loads a single feed file or every `nvdcve-2.0-*` feed in a directory.
+    pub fn load(path: &str) -> NvdApi {
+        let mut nvd_api = NvdApi::new();
+        let target = Path::new(path);
+        let mut files = Vec::new();
+        if target.is_dir() {
+            match fs::read_dir(target) {
+                Ok(entries) => {
+                    for entry in entries.flatten() {
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        if name.starts_with("nvdcve-2.0-")
+                            && (name.ends_with(".json") || name.ends_with(".json.gz"))
+                        {
+                            files.push(entry.path());
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("read nvd dir error: {:#?}", e);
+                }
+            }
+            files.sort();
+        } else {
+            files.push(target.to_path_buf());
+        }
+
+        println!("load nvd feeds: {:#?}", files);
+        for file in files.iter() {
+            match NvdApi::read_feed(file) {
+                Ok(cves) => {
+                    for cve in cves {
+                        nvd_api.cves.insert(cve.id.clone(), cve);
+                    }
+                }
+                Err(e) => {
+                    println!("read nvd feed {:?} error: {:#?}", file, e);
+                }
+            }
+        }
+        nvd_api
+    }
+
+    fn read_feed(path: &Path) -> Result<Vec<CveRecord>, Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+        let mut data = String::new();
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut data)?;
+        } else {
+            data = String::from_utf8(bytes)?;
+        }
+        NvdApi::parser_feed(&data)
+    }
+
+    fn parser_feed(data: &str) -> Result<Vec<CveRecord>, Box<dyn Error>> {
+        let feed: NvdFeed = serde_json::from_str(data)?;
+        Ok(feed
+            .vulnerabilities
+            .into_iter()
+            .map(|x| CveRecord::from(x.cve))
+            .collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.cves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cves.is_empty()
+    }
+}
+
+impl Default for NvdApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CveApi for NvdApi {
+    fn query(&self, id: &str) -> CveRecord {
+        self.cves.get(id).cloned().unwrap_or_default()
+    }
+
+    fn id(&self) -> String {
+        String::from(NVD_CVE_API)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NvdFeed {
+    #[serde(default)]
+    vulnerabilities: Vec<NvdVulnerability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NvdVulnerability {
+    cve: NvdCveItem,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NvdCveItem {
+    id: String,
+    #[serde(default)]
+    published: String,
+    #[serde(default)]
+    last_modified: String,
+    #[serde(default)]
+    descriptions: Vec<NvdDescription>,
+    #[serde(default)]
+    metrics: NvdMetrics,
+    #[serde(default)]
+    weaknesses: Vec<NvdWeakness>,
+    #[serde(default)]
+    references: Vec<NvdReference>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NvdDescription {
+    lang: String,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NvdWeakness {
+    #[serde(default)]
+    description: Vec<NvdDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NvdReference {
+    url: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct NvdMetrics {
+    #[serde(default, rename = "cvssMetricV31")]
+    cvss_metric_v31: Vec<NvdCvssMetric>,
+    #[serde(default, rename = "cvssMetricV30")]
+    cvss_metric_v30: Vec<NvdCvssMetric>,
+    #[serde(default, rename = "cvssMetricV40")]
+    cvss_metric_v40: Vec<NvdCvssMetric>,
+    #[serde(default, rename = "cvssMetricV2")]
+    cvss_metric_v2: Vec<NvdCvssMetric>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NvdCvssMetric {
+    #[serde(default, rename = "type")]
+    metric_type: String,
+    cvss_data: NvdCvssData,
+    // cvss v2 keeps the severity next to cvssData
+    base_severity: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NvdCvssData {
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    vector_string: String,
+    base_score: f64,
+    base_severity: Option<String>,
+}
+
+impl From<NvdCveItem> for CveRecord {
+    fn from(item: NvdCveItem) -> Self {
+        let mut cve = CveRecord::new();
+        cve.id = item.id;
+        cve.published = parse_date(&item.published);
+        cve.modified = parse_date(&item.last_modified);
+
+        let description = item
+            .descriptions
+            .iter()
+            .find(|x| x.lang == "en")
+            .or_else(|| item.descriptions.first());
+        if let Some(description) = description {
+            cve.description = description.value.trim().to_string();
+            // NVD has no title, use the first sentence of the description instead
+            cve.title = match cve.description.find(". ") {
+                Some(end) => cve.description[..end].to_string(),
+                None => cve.description.trim_end_matches('.').to_string(),
+            };
+        }
+
+        let metrics = &item.metrics;
+        for versions in [
+            &metrics.cvss_metric_v31,
+            &metrics.cvss_metric_v30,
+            &metrics.cvss_metric_v40,
+            &metrics.cvss_metric_v2,
+        ] {
+            let metric = versions
+                .iter()
+                .find(|x| x.metric_type == "Primary")
+                .or_else(|| versions.first());
+            if let Some(metric) = metric {
+                let data = &metric.cvss_data;
+                cve.cvss.push(CvssMetric::new(&data.version, &data.vector_string, Some(data.base_score)));
+                if cve.severity.is_none() {
+                    cve.severity = data
+                        .base_severity
+                        .as_ref()
+                        .or(metric.base_severity.as_ref())
+                        .and_then(|x| Severity::parse(x));
+                }
+            }
+        }
+
+        for weakness in item.weaknesses.iter() {
+            for description in weakness.description.iter() {
+                if description.value.starts_with("CWE-") && !cve.cwe.contains(&description.value) {
+                    cve.cwe.push(description.value.clone());
+                }
+            }
+        }
+        for reference in item.references {
+            if !cve.references.contains(&reference.url) {
+                cve.references.push(reference.url);
+            }
+        }
+        cve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const NVD_FEED_STR: &str = r#"{
+        "format": "NVD_CVE",
+        "version": "2.0",
+        "vulnerabilities": [{
+            "cve": {
+                "id": "CVE-2023-25194",
+                "published": "2023-02-07T20:15:17.107",
+                "lastModified": "2024-02-04T09:15:09.113",
+                "descriptions": [
+                    {"lang": "es", "value": "Una vulnerabilidad"},
+                    {"lang": "en", "value": "A possible security vulnerability has been identified in Apache Kafka Connect API. This requires access to a Kafka Connect worker."}
+                ],
+                "metrics": {
+                    "cvssMetricV31": [
+                        {"source": "nvd@nist.gov", "type": "Primary", "cvssData": {"version": "3.1", "vectorString": "CVSS:3.1/AV:N/AC:L/PR:H/UI:N/S:U/C:H/I:H/A:H", "baseScore": 7.2, "baseSeverity": "HIGH"}}
+                    ],
+                    "cvssMetricV2": [
+                        {"source": "nvd@nist.gov", "type": "Primary", "cvssData": {"version": "2.0", "vectorString": "AV:N/AC:L/Au:S/C:C/I:C/A:C", "baseScore": 9.0}, "baseSeverity": "HIGH"}
+                    ]
+                },
+                "weaknesses": [{"source": "nvd@nist.gov", "type": "Primary", "description": [{"lang": "en", "value": "CWE-502"}]}],
+                "references": [{"url": "https://hackerone.com/reports/1529790"}]
+            }
+        }]
+    }"#;
+
+    #[test]
+    fn test_parser_feed() {
+        let cves = NvdApi::parser_feed(NVD_FEED_STR).unwrap();
+        assert_eq!(cves.len(), 1);
+        let cve = &cves[0];
+        assert_eq!(cve.id, "CVE-2023-25194");
+        assert_eq!(cve.published, parse_date("2023-02-07"));
+        assert_eq!(cve.modified, parse_date("2024-02-04"));
+        assert_eq!(
+            cve.title,
+            "A possible security vulnerability has been identified in Apache Kafka Connect API"
+        );
+        assert_eq!(cve.score(), Some(7.2));
+        assert_eq!(cve.vector(), Some("CVSS:3.1/AV:N/AC:L/PR:H/UI:N/S:U/C:H/I:H/A:H"));
+        assert_eq!(cve.cvss[1].version, "2.0");
+        assert_eq!(cve.severity, Some(Severity::High));
+        assert_eq!(cve.cwe, vec!["CWE-502"]);
+        assert_eq!(cve.references.len(), 1);
+    }
+
+    #[test]
+    fn test_load_gz_feed() {
+        let dir = std::env::temp_dir().join("etool-nvd-test");
+        fs::create_dir_all(&dir).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(NVD_FEED_STR.as_bytes()).unwrap();
+        fs::write(dir.join("nvdcve-2.0-2023.json.gz"), encoder.finish().unwrap()).unwrap();
+
+        let nvd_api = NvdApi::load(dir.to_str().unwrap());
+        assert_eq!(nvd_api.len(), 1);
+        assert_eq!(nvd_api.query("CVE-2023-25194").score(), Some(7.2));
+        assert!(nvd_api.query("CVE-2000-0001").is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
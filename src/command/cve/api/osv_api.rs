@@ -0,0 +1,688 @@
+use crate::command::cve::api::cvss::Cvss;
+use crate::command::cve::api::lib::CveApi;
+use crate::command::cve::api::record::{parse_date, CveRecord, CvssMetric, Severity};
+use crate::command::cve::api::version::VersionRange;
+use serde::{self, Deserialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const OSV_CVE_API: &str = "OsvApi";
+
+/// Answers queries from the osv.dev per-ecosystem exports (`<ecosystem>/all.zip`),
+/// resolving CVE ids through the `aliases` of each record.
+pub struct OsvApi {
+    records: HashMap<String, Vec<OsvRecord>>,
+    packages: OsvPackageIndex,
+}
+
+impl OsvApi {
+    pub fn new() -> OsvApi {
+        OsvApi {
+            records: HashMap::new(),
+            packages: OsvPackageIndex::default(),
+        }
+    }
+
+    /// Loads a single zip export or every zip export below a directory.
+    pub fn load(path: &str) -> OsvApi {
+        let mut osv_api = OsvApi::new();
+        let mut files = Vec::new();
+        find_files(Path::new(path), ".zip", &mut files);
+        files.sort();
+
+        println!("load osv exports: {:#?}", files);
+        for file in files.iter() {
+            match OsvApi::read_zip(file) {
+                Ok(records) => {
+                    for record in records {
+                        osv_api.insert(record);
+                    }
+                }
+                Err(e) => {
+                    println!("read osv export {:?} error: {:#?}", file, e);
+                }
+            }
+        }
+        osv_api
+    }
+
+    fn read_zip(path: &Path) -> Result<Vec<OsvRecord>, Box<dyn Error>> {
+        let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+        let mut records = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            if !entry.name().ends_with(".json") {
+                continue;
+            }
+            let mut data = String::new();
+            entry.read_to_string(&mut data)?;
+            match OsvRecord::parse(&data) {
+                Ok(record) => records.push(record),
+                Err(e) => {
+                    println!("parse osv record {} error: {:#?}", entry.name(), e);
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    pub(crate) fn insert(&mut self, record: OsvRecord) {
+        self.packages.insert(&record);
+        for id in record.cve_ids() {
+            self.records.entry(id).or_default().push(record.clone());
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl Default for OsvApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CveApi for OsvApi {
+    fn query(&self, id: &str) -> CveRecord {
+        match self.records.get(id) {
+            Some(records) => osv_cve_record(id, records),
+            None => CveRecord::new(),
+        }
+    }
+
+    fn id(&self) -> String {
+        String::from(OSV_CVE_API)
+    }
+
+    fn query_package(&self, purl: &str, version: &str) -> Vec<String> {
+        self.packages.query(purl, version)
+    }
+}
+
+/// Package url types and the OSV ecosystem of their packages.
+const PURL_ECOSYSTEMS: [(&str, &str); 10] = [
+    ("maven", "Maven"),
+    ("npm", "npm"),
+    ("pypi", "PyPI"),
+    ("golang", "Go"),
+    ("cargo", "crates.io"),
+    ("gem", "RubyGems"),
+    ("nuget", "NuGet"),
+    ("composer", "Packagist"),
+    ("hex", "Hex"),
+    ("pub", "Pub"),
+];
+
+/// Distribution ids of the `distro` qualifier or the deb/apk/rpm namespace and their OSV ecosystem.
+const DISTRO_ECOSYSTEMS: [(&str, &str); 9] = [
+    ("debian", "Debian"),
+    ("ubuntu", "Ubuntu"),
+    ("alpine", "Alpine"),
+    ("rocky", "Rocky Linux"),
+    ("almalinux", "AlmaLinux"),
+    ("rhel", "Red Hat"),
+    ("redhat", "Red Hat"),
+    ("opensuse", "openSUSE"),
+    ("sles", "SUSE"),
+];
+
+/// The OSV package ids (`ecosystem/name`, lowercase), most specific first, and the version of a
+/// package url like `pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1`, `None` for unknown
+/// types. OS packages like `pkg:deb/debian/libssl1.1@1.1.1n-0?upstream=openssl&distro=debian-11`
+/// are looked up by their source package, in the release of the `distro` qualifier first.
+pub(crate) fn purl_package(purl: &str) -> Option<(Vec<String>, String)> {
+    let purl = purl.trim().strip_prefix("pkg:")?;
+    let purl = purl.split('#').next().unwrap_or_default();
+    let (purl, qualifiers) = purl.split_once('?').unwrap_or((purl, ""));
+    let qualifier = |key: &str| {
+        qualifiers
+            .split('&')
+            .find_map(|x| x.strip_prefix(key)?.strip_prefix('='))
+            .map(percent_decode)
+    };
+    let (purl, version) = purl.rsplit_once('@').unwrap_or((purl, ""));
+    let (purl_type, path) = purl.split_once('/')?;
+    let purl_type = purl_type.to_lowercase();
+    let path = percent_decode(path);
+    let version = percent_decode(version);
+
+    if ["deb", "apk", "rpm"].contains(&purl_type.as_str()) {
+        let (namespace, name) = path.rsplit_once('/').unwrap_or(("", &path));
+        let distro = qualifier("distro").unwrap_or_default();
+        let (distro_id, release) = distro.split_once('-').unwrap_or((&distro, ""));
+        let distro_id = if distro_id.is_empty() { namespace } else { distro_id };
+        let ecosystem = DISTRO_ECOSYSTEMS
+            .iter()
+            .find(|x| x.0 == distro_id.to_lowercase())?
+            .1;
+        // deb and apk advisories name the source package, rpm ones the binary package
+        let name = match (purl_type.as_str(), qualifier("upstream")) {
+            ("rpm", _) | (_, None) => name.to_string(),
+            (_, Some(upstream)) => upstream.split('@').next().unwrap_or_default().to_string(),
+        };
+        let mut release_parts = release.split('.');
+        let release = match (ecosystem, release_parts.next(), release_parts.next()) {
+            (_, None, _) | (_, Some(""), _) => None,
+            ("Alpine", Some(major), Some(minor)) => Some(format!("v{}.{}", major, minor)),
+            ("Debian", Some(major), _) | ("Rocky Linux", Some(major), _) | ("AlmaLinux", Some(major), _) => {
+                Some(major.to_string())
+            }
+            _ => None,
+        };
+        let mut ids = Vec::new();
+        if let Some(release) = release {
+            ids.push(format!("{}:{}/{}", ecosystem, release, name).to_lowercase());
+        }
+        ids.push(format!("{}/{}", ecosystem, name).to_lowercase());
+        return Some((ids, version));
+    }
+
+    let ecosystem = PURL_ECOSYSTEMS.iter().find(|x| x.0 == purl_type)?.1;
+    let name = match (purl_type.as_str(), path.rsplit_once('/')) {
+        // maven names are `group:artifact`
+        ("maven", Some((group, artifact))) => format!("{}:{}", group, artifact),
+        ("pypi", _) => path.replace('_', "-"),
+        _ => path,
+    };
+    Some((vec![format!("{}/{}", ecosystem, name).to_lowercase()], version))
+}
+
+/// Decodes the `%XX` escapes of a purl component.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|x| std::str::from_utf8(x).ok());
+        match (bytes[i], hex.and_then(|x| u8::from_str_radix(x, 16).ok())) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// The affected packages of OSV records, to find the vulnerabilities of a package version.
+#[derive(Default)]
+pub(crate) struct OsvPackageIndex {
+    packages: HashMap<String, Vec<(String, OsvAffected)>>,
+}
+
+impl OsvPackageIndex {
+    /// Indexes the affected packages of `record` under its CVE ids, or its own id without any.
+    pub fn insert(&mut self, record: &OsvRecord) {
+        let mut ids = record.cve_ids();
+        if ids.is_empty() {
+            ids.push(record.id.clone());
+        }
+        for affected in record.affected.iter() {
+            let package_id = affected.package_id().to_lowercase();
+            let mut keys = vec![package_id.clone()];
+            // a release ecosystem like `Debian:11` also answers purls without a distro release
+            if let Some((ecosystem, name)) = package_id.split_once('/') {
+                if let Some((base, _)) = ecosystem.split_once(':') {
+                    keys.push(format!("{}/{}", base, name));
+                }
+            }
+            for key in keys {
+                let entries = self.packages.entry(key).or_default();
+                for id in ids.iter() {
+                    entries.push((id.clone(), affected.clone()));
+                }
+            }
+        }
+    }
+
+    /// The vulnerability ids affecting `version` of `purl`, the purl version is used when
+    /// `version` is empty. The most specific package id the index has is asked.
+    pub fn query(&self, purl: &str, version: &str) -> Vec<String> {
+        let (packages, purl_version) = match purl_package(purl) {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+        let version = if version.is_empty() { purl_version.as_str() } else { version };
+        let mut ids: Vec<String> = Vec::new();
+        let entries = packages.iter().find_map(|x| self.packages.get(x));
+        for (id, affected) in entries.into_iter().flatten() {
+            if !ids.contains(id) && affected.contains_version(version) {
+                ids.push(id.clone());
+            }
+        }
+        ids
+    }
+}
+
+/// Collects every file below `path` whose name ends with `suffix`.
+pub(crate) fn find_files(path: &Path, suffix: &str, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    find_files(&entry.path(), suffix, files);
+                }
+            }
+            Err(e) => {
+                println!("read dir {:?} error: {:#?}", path, e);
+            }
+        }
+    } else if path.to_string_lossy().ends_with(suffix) {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// A vulnerability in the OSV schema, shared with the GitHub advisory database.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct OsvRecord {
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub details: String,
+    #[serde(default)]
+    pub published: String,
+    #[serde(default)]
+    pub modified: String,
+    #[serde(default)]
+    pub severity: Vec<OsvSeverity>,
+    #[serde(default)]
+    pub affected: Vec<OsvAffected>,
+    #[serde(default)]
+    pub references: Vec<OsvReference>,
+    #[serde(default)]
+    pub database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct OsvSeverity {
+    #[serde(rename = "type")]
+    pub severity_type: String,
+    pub score: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct OsvAffected {
+    #[serde(default)]
+    pub package: OsvPackage,
+    #[serde(default)]
+    pub ranges: Vec<OsvRange>,
+    #[serde(default)]
+    pub versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct OsvPackage {
+    #[serde(default)]
+    pub ecosystem: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct OsvRange {
+    #[serde(rename = "type")]
+    pub range_type: String,
+    #[serde(default)]
+    pub events: Vec<OsvEvent>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct OsvEvent {
+    pub introduced: Option<String>,
+    pub fixed: Option<String>,
+    pub last_affected: Option<String>,
+    pub limit: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct OsvReference {
+    pub url: String,
+}
+
+impl OsvRecord {
+    pub fn parse(data: &str) -> Result<OsvRecord, Box<dyn Error>> {
+        Ok(serde_json::from_str(data)?)
+    }
+
+    /// The CVE ids this record answers for, taken from its id and aliases.
+    pub fn cve_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for id in [&self.id].into_iter().chain(self.aliases.iter()) {
+            if id.starts_with("CVE-") && !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+        ids
+    }
+
+    /// The `severity` of GHSA-style `database_specific` blocks.
+    pub fn database_severity(&self) -> String {
+        self.database_specific
+            .as_ref()
+            .and_then(|x| x.get("severity"))
+            .and_then(|x| x.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub fn cwe_ids(&self) -> Vec<String> {
+        match self
+            .database_specific
+            .as_ref()
+            .and_then(|x| x.get("cwe_ids"))
+            .and_then(|x| x.as_array())
+        {
+            Some(v) => v
+                .iter()
+                .filter_map(|x| x.as_str().map(|x| x.to_string()))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+impl OsvSeverity {
+    /// The CVSS version of `CVSS_V2`, `CVSS_V3` and `CVSS_V4` scores, `None` for other types.
+    pub fn cvss_version(&self) -> Option<String> {
+        if !self.severity_type.starts_with("CVSS") {
+            return None;
+        }
+        let version = match self.score.strip_prefix("CVSS:") {
+            Some(v) => v.split('/').next().unwrap_or_default(),
+            None if self.severity_type == "CVSS_V2" => "2.0",
+            None => "",
+        };
+        Some(version.to_string())
+    }
+}
+
+impl OsvAffected {
+    /// The package as `ecosystem/name`, e.g. `Go/golang.org/x/net`.
+    pub fn package_id(&self) -> String {
+        format!("{}/{}", self.package.ecosystem, self.package.name)
+    }
+
+    /// Human readable affected ranges, e.g. `>=1.0.0, <1.2.3`.
+    pub fn affected_ranges(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        for range in self.ranges.iter().filter(|x| x.range_type != "GIT") {
+            let mut introduced = String::new();
+            for event in range.events.iter() {
+                if let Some(v) = &event.introduced {
+                    introduced = v.clone();
+                }
+                let end = if let Some(v) = &event.fixed {
+                    format!("<{}", v)
+                } else if let Some(v) = &event.last_affected {
+                    format!("<={}", v)
+                } else if let Some(v) = &event.limit {
+                    format!("<{}", v)
+                } else {
+                    continue;
+                };
+                if introduced.is_empty() || introduced == "0" {
+                    result.push(end);
+                } else {
+                    result.push(format!(">={}, {}", introduced, end));
+                }
+                introduced.clear();
+            }
+            // introduced without an end: every later version is affected
+            if !introduced.is_empty() {
+                if introduced == "0" {
+                    result.push(String::from("*"));
+                } else {
+                    result.push(format!(">={}", introduced));
+                }
+            }
+        }
+        if result.is_empty() && !self.versions.is_empty() {
+            result.push(self.versions.join(", "));
+        }
+        result
+    }
+
+    /// Whether `version` is listed in `versions` or falls into a non-GIT range.
+    pub fn contains_version(&self, version: &str) -> bool {
+        if version.is_empty() {
+            return false;
+        }
+        if self.versions.iter().any(|x| x == version) {
+            return true;
+        }
+        for range in self.ranges.iter().filter(|x| x.range_type != "GIT") {
+            let mut introduced: Option<&str> = None;
+            for event in range.events.iter() {
+                if let Some(v) = &event.introduced {
+                    introduced = Some(v);
+                }
+                let end = if let Some(v) = &event.fixed {
+                    (v.as_str(), false)
+                } else if let Some(v) = &event.last_affected {
+                    (v.as_str(), true)
+                } else if let Some(v) = &event.limit {
+                    (v.as_str(), false)
+                } else {
+                    continue;
+                };
+                if VersionRange::from_events(introduced.unwrap_or_default(), Some(end)).contains(version) {
+                    return true;
+                }
+                introduced = None;
+            }
+            if let Some(v) = introduced {
+                if VersionRange::from_events(v, None).contains(version) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn fixed_versions(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        for range in self.ranges.iter().filter(|x| x.range_type != "GIT") {
+            for event in range.events.iter() {
+                if let Some(v) = &event.fixed {
+                    if !result.contains(v) {
+                        result.push(v.clone());
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Combines every record aliased to the same CVE id into one result.
+pub(crate) fn osv_cve_record(id: &str, records: &[OsvRecord]) -> CveRecord {
+    let mut cve = CveRecord::new();
+    if records.is_empty() {
+        return cve;
+    }
+    cve.id = id.to_string();
+    for record in records.iter() {
+        for alias in [&record.id].into_iter().chain(record.aliases.iter()) {
+            if alias != id && !cve.aliases.contains(alias) {
+                cve.aliases.push(alias.clone());
+            }
+        }
+        if cve.title.is_empty() {
+            cve.title = record.summary.trim().to_string();
+        }
+        if cve.description.is_empty() {
+            cve.description = record.details.trim().to_string();
+        }
+        if cve.published.is_none() {
+            cve.published = parse_date(&record.published);
+        }
+        if cve.modified.is_none() {
+            cve.modified = parse_date(&record.modified);
+        }
+        if cve.severity.is_none() {
+            cve.severity = Severity::parse(&record.database_severity());
+        }
+        for severity in record.severity.iter() {
+            if let Some(version) = severity.cvss_version() {
+                if !cve.cvss.iter().any(|x| x.vector == severity.score) {
+                    let score = Cvss::parse(&severity.score).ok().map(|x| x.base_score());
+                    cve.cvss.push(CvssMetric::new(&version, &severity.score, score));
+                }
+            }
+        }
+        for id in record.cwe_ids() {
+            if !cve.cwe.contains(&id) {
+                cve.cwe.push(id);
+            }
+        }
+        for item in record.affected.iter() {
+            let line = format!("{}: {}", item.package_id(), item.affected_ranges().join(" || "));
+            if !cve.affected.contains(&line) {
+                cve.affected.push(line);
+            }
+            for version in item.fixed_versions() {
+                let line = format!("{} {}", item.package_id(), version);
+                if !cve.fixed.contains(&line) {
+                    cve.fixed.push(line);
+                }
+            }
+        }
+        for reference in record.references.iter() {
+            if !cve.references.contains(&reference.url) {
+                cve.references.push(reference.url.clone());
+            }
+        }
+    }
+    cve
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use std::io::Write;
+
+    const OSV_RECORD_STR: &str = r#"{
+        "id": "GO-2022-0969",
+        "published": "2022-09-12T20:23:06Z",
+        "aliases": ["CVE-2022-27664", "GHSA-69cg-p879-7622"],
+        "summary": "Denial of service in net/http and golang.org/x/net/http2",
+        "details": "HTTP/2 server connections can hang forever waiting for a clean shutdown.",
+        "affected": [{
+            "package": {"name": "golang.org/x/net", "ecosystem": "Go"},
+            "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "0.0.0-20220906165146-f3363e06e74c"}]}]
+        }, {
+            "package": {"name": "stdlib", "ecosystem": "Go"},
+            "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "1.18.6"}, {"introduced": "1.19.0"}, {"fixed": "1.19.1"}]}]
+        }],
+        "references": [{"type": "FIX", "url": "https://go.dev/cl/428735"}]
+    }"#;
+
+    const DEBIAN_RECORD_STR: &str = r#"{
+        "id": "DSA-5343-1",
+        "aliases": ["CVE-2023-0286"],
+        "affected": [{
+            "package": {"name": "openssl", "ecosystem": "Debian:11"},
+            "ranges": [{"type": "ECOSYSTEM", "events": [{"introduced": "0"}, {"fixed": "1.1.1n-0+deb11u4"}]}]
+        }]
+    }"#;
+
+    #[test]
+    fn test_affected_ranges() {
+        let record = OsvRecord::parse(OSV_RECORD_STR).unwrap();
+        assert_eq!(record.cve_ids(), vec!["CVE-2022-27664"]);
+        assert_eq!(
+            record.affected[1].affected_ranges(),
+            vec!["<1.18.6", ">=1.19.0, <1.19.1"]
+        );
+        assert_eq!(record.affected[1].fixed_versions(), vec!["1.18.6", "1.19.1"]);
+        assert_eq!(record.affected[1].package_id(), "Go/stdlib");
+    }
+
+    #[test]
+    fn test_query_package() {
+        assert_eq!(
+            purl_package("pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1?type=jar"),
+            Some((vec![String::from("maven/org.apache.logging.log4j:log4j-core")], String::from("2.14.1")))
+        );
+        assert_eq!(
+            purl_package("pkg:npm/%40babel/core@7.0.0"),
+            Some((vec![String::from("npm/@babel/core")], String::from("7.0.0")))
+        );
+        assert_eq!(
+            purl_package("pkg:deb/debian/libssl1.1@1.1.1n-0%2Bdeb11u3?arch=amd64&upstream=openssl&distro=debian-11.6"),
+            Some((
+                vec![String::from("debian:11/openssl"), String::from("debian/openssl")],
+                String::from("1.1.1n-0+deb11u3")
+            ))
+        );
+        assert_eq!(
+            purl_package("pkg:apk/alpine/busybox@1.36.1-r2?distro=alpine-3.18.4").unwrap().0,
+            vec!["alpine:v3.18/busybox", "alpine/busybox"]
+        );
+        assert_eq!(
+            purl_package("pkg:rpm/rocky/openssl-libs@1.1.1k-9.el8?distro=rocky-8.8").unwrap().0,
+            vec!["rocky linux:8/openssl-libs", "rocky linux/openssl-libs"]
+        );
+        assert_eq!(purl_package("pkg:deb/acme/openssl@1.1.1"), None);
+
+        let mut osv_api = OsvApi::new();
+        osv_api.insert(OsvRecord::parse(OSV_RECORD_STR).unwrap());
+        assert_eq!(osv_api.query_package("pkg:golang/stdlib@1.18.5", ""), vec!["CVE-2022-27664"]);
+        assert_eq!(osv_api.query_package("pkg:golang/stdlib", "1.19.0"), vec!["CVE-2022-27664"]);
+        assert_eq!(osv_api.query_package("pkg:golang/stdlib@v1.18.5", ""), vec!["CVE-2022-27664"]);
+        assert!(osv_api.query_package("pkg:golang/stdlib@1.19.1", "").is_empty());
+        assert!(osv_api.query_package("pkg:golang/golang.org/x/net", "").is_empty());
+
+        osv_api.insert(OsvRecord::parse(DEBIAN_RECORD_STR).unwrap());
+        let purl = "pkg:deb/debian/libssl1.1@1.1.1n-0%2Bdeb11u3?upstream=openssl&distro=debian-11";
+        assert_eq!(osv_api.query_package(purl, ""), vec!["CVE-2023-0286"]);
+        assert!(osv_api.query_package(purl, "1.1.1n-0+deb11u4").is_empty());
+        // without a release every Debian release is asked
+        assert_eq!(osv_api.query_package("pkg:deb/debian/openssl@1.1.1n-0+deb11u3", ""), vec!["CVE-2023-0286"]);
+    }
+
+    #[test]
+    fn test_load_zip() {
+        let dir = std::env::temp_dir().join("etool-osv-test/Go");
+        fs::create_dir_all(&dir).unwrap();
+        let mut writer = zip::ZipWriter::new(fs::File::create(dir.join("all.zip")).unwrap());
+        writer
+            .start_file("GO-2022-0969.json", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(OSV_RECORD_STR.as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let osv_api = OsvApi::load(dir.parent().unwrap().to_str().unwrap());
+        assert_eq!(osv_api.len(), 1);
+        let cve = osv_api.query("CVE-2022-27664");
+        assert_eq!(cve.aliases, vec!["GO-2022-0969", "GHSA-69cg-p879-7622"]);
+        assert_eq!(cve.published, parse_date("2022-09-12"));
+        assert_eq!(
+            cve.fixed,
+            vec![
+                "Go/golang.org/x/net 0.0.0-20220906165146-f3363e06e74c",
+                "Go/stdlib 1.18.6",
+                "Go/stdlib 1.19.1"
+            ]
+        );
+        assert!(osv_api.query("CVE-2000-0001").is_empty());
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+}
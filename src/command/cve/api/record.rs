@@ -0,0 +1,324 @@
+use crate::command::cve::api::cvss::Cvss;
+use chrono::NaiveDate;
+use serde::{self, Deserialize, Serialize};
+use std::fmt;
+
+/// Every field of a `CveRecord`, in the order the provider chain reports them.
+pub const CVE_FIELDS: [&str; 14] = [
+    "id",
+    "aliases",
+    "title",
+    "fix_label",
+    "published",
+    "modified",
+    "description",
+    "suggestion",
+    "cvss",
+    "severity",
+    "cwe",
+    "affected",
+    "fixed",
+    "references",
+];
+
+/// The provider independent view of a CVE every `CveApi` answers with.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct CveRecord {
+    pub id: String,
+    /// Other identifiers of the same vulnerability, e.g. GHSA or GO ids.
+    pub aliases: Vec<String>,
+    pub title: String,
+    /// The fix hint scraped from Aliyun, e.g. `官方补丁`.
+    pub fix_label: String,
+    pub published: Option<NaiveDate>,
+    pub modified: Option<NaiveDate>,
+    pub description: String,
+    pub suggestion: String,
+    /// Preferred metric first.
+    pub cvss: Vec<CvssMetric>,
+    pub severity: Option<Severity>,
+    pub cwe: Vec<String>,
+    /// One `product: ranges` line per affected product.
+    pub affected: Vec<String>,
+    /// One `product version` line per fixed version.
+    pub fixed: Vec<String>,
+    pub references: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CvssMetric {
+    /// `2.0`, `3.0`, `3.1` or `4.0`, empty when unknown.
+    pub version: String,
+    pub vector: String,
+    pub score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum Severity {
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixStatus {
+    Unknown,
+    Fixed,
+    Unfixed,
+}
+
+impl CveRecord {
+    pub fn new() -> CveRecord {
+        CveRecord::default()
+    }
+
+    /// Fills every blank field from `other` and returns the names of the fields taken.
+    pub fn merge(&mut self, other: &CveRecord) -> Vec<&'static str> {
+        let mut taken = Vec::new();
+        if fill(&mut self.id, &other.id) {
+            taken.push("id");
+        }
+        if fill(&mut self.aliases, &other.aliases) {
+            taken.push("aliases");
+        }
+        if fill(&mut self.title, &other.title) {
+            taken.push("title");
+        }
+        if fill(&mut self.fix_label, &other.fix_label) {
+            taken.push("fix_label");
+        }
+        if fill(&mut self.published, &other.published) {
+            taken.push("published");
+        }
+        if fill(&mut self.modified, &other.modified) {
+            taken.push("modified");
+        }
+        if fill(&mut self.description, &other.description) {
+            taken.push("description");
+        }
+        if fill(&mut self.suggestion, &other.suggestion) {
+            taken.push("suggestion");
+        }
+        if fill(&mut self.cvss, &other.cvss) {
+            taken.push("cvss");
+        }
+        if fill(&mut self.severity, &other.severity) {
+            taken.push("severity");
+        }
+        if fill(&mut self.cwe, &other.cwe) {
+            taken.push("cwe");
+        }
+        if fill(&mut self.affected, &other.affected) {
+            taken.push("affected");
+        }
+        if fill(&mut self.fixed, &other.fixed) {
+            taken.push("fixed");
+        }
+        if fill(&mut self.references, &other.references) {
+            taken.push("references");
+        }
+        taken
+    }
+
+    /// True when no field is set, e.g. for an unknown id.
+    pub fn is_empty(&self) -> bool {
+        CveRecord::new().merge(self).is_empty()
+    }
+
+    /// The score of the preferred metric, calculated from its vector when no provider reports one.
+    pub fn score(&self) -> Option<f64> {
+        self.cvss.iter().find_map(|x| x.base_score())
+    }
+
+    pub fn vector(&self) -> Option<&str> {
+        self.cvss
+            .iter()
+            .map(|x| x.vector.as_str())
+            .find(|x| !x.is_empty())
+    }
+
+    /// The reported severity, derived from the preferred metric when no provider reports one.
+    pub fn severity(&self) -> Option<Severity> {
+        self.severity
+            .or_else(|| self.cvss.iter().find_map(|x| x.cvss()).map(|x| x.severity()))
+            .or_else(|| self.score().map(Severity::from_score))
+    }
+
+    pub fn fix_status(&self) -> FixStatus {
+        if !self.fixed.is_empty() {
+            return FixStatus::Fixed;
+        }
+        let label = self.fix_label.trim();
+        if label.is_empty() {
+            FixStatus::Unknown
+        } else if label.contains("暂无") || label.contains("无补丁") || label.contains("未修复") {
+            FixStatus::Unfixed
+        } else {
+            FixStatus::Fixed
+        }
+    }
+
+    /// `fix_label`, or the fixed versions for providers without one.
+    pub fn fix_hint(&self) -> String {
+        if self.fix_label.trim().is_empty() {
+            self.fixed.join("\n")
+        } else {
+            self.fix_label.clone()
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self).unwrap()
+    }
+}
+
+impl CvssMetric {
+    pub fn new(version: &str, vector: &str, score: Option<f64>) -> CvssMetric {
+        CvssMetric {
+            version: version.to_string(),
+            vector: vector.to_string(),
+            score,
+        }
+    }
+
+    /// The parsed vector, `None` when it is missing or invalid.
+    pub fn cvss(&self) -> Option<Cvss> {
+        Cvss::parse(&self.vector).ok()
+    }
+
+    pub fn base_score(&self) -> Option<f64> {
+        self.score.or_else(|| self.cvss().map(|x| x.base_score()))
+    }
+}
+
+impl Severity {
+    /// Accepts the English ratings and the Chinese ones of Aliyun, e.g. `高危`.
+    pub fn parse(s: &str) -> Option<Severity> {
+        let s = s.trim();
+        match s.to_ascii_uppercase().as_str() {
+            "NONE" | "无" => Some(Severity::None),
+            "LOW" | "低" | "低危" => Some(Severity::Low),
+            "MEDIUM" | "MODERATE" | "中" | "中危" => Some(Severity::Medium),
+            "HIGH" | "IMPORTANT" | "高" | "高危" => Some(Severity::High),
+            "CRITICAL" | "严重" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    /// The CVSS v3 rating of `score`.
+    pub fn from_score(score: f64) -> Severity {
+        if score >= 9.0 {
+            Severity::Critical
+        } else if score >= 7.0 {
+            Severity::High
+        } else if score >= 4.0 {
+            Severity::Medium
+        } else if score > 0.0 {
+            Severity::Low
+        } else {
+            Severity::None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::None => "NONE",
+            Severity::Low => "LOW",
+            Severity::Medium => "MEDIUM",
+            Severity::High => "HIGH",
+            Severity::Critical => "CRITICAL",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The date part of `2023-02-07T20:15:17.107` like timestamps.
+pub fn parse_date(s: &str) -> Option<NaiveDate> {
+    let s = s.trim();
+    let date = s.split(['T', ' ']).next().unwrap_or_default();
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+trait Blank {
+    fn is_blank(&self) -> bool;
+}
+
+impl Blank for String {
+    fn is_blank(&self) -> bool {
+        self.trim().is_empty()
+    }
+}
+
+impl<T> Blank for Vec<T> {
+    fn is_blank(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> Blank for Option<T> {
+    fn is_blank(&self) -> bool {
+        self.is_none()
+    }
+}
+
+fn fill<T: Blank + Clone>(value: &mut T, other: &T) -> bool {
+    if value.is_blank() && !other.is_blank() {
+        *value = other.clone();
+        return true;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut record = CveRecord::new();
+        record.title = String::from("nvd title");
+        record.fix_label = String::from(" ");
+        let mut other = CveRecord::new();
+        other.title = String::from("aliyun title");
+        other.fix_label = String::from("官方补丁");
+        other.cvss = vec![CvssMetric::new("", "", Some(9.8))];
+
+        assert_eq!(record.merge(&other), vec!["fix_label", "cvss"]);
+        assert_eq!(record.title, "nvd title");
+        assert_eq!(record.fix_status(), FixStatus::Fixed);
+        assert_eq!(record.severity(), Some(Severity::Critical));
+        assert!(CveRecord::new().is_empty());
+        assert!(!record.is_empty());
+
+        let mut record = CveRecord::new();
+        record.cvss = vec![CvssMetric::new("2.0", "AV:N/AC:L/Au:N/C:C/I:C/A:C", None)];
+        assert_eq!(record.score(), Some(10.0));
+        assert_eq!(record.severity(), Some(Severity::High));
+    }
+
+    #[test]
+    fn test_severity() {
+        assert_eq!(Severity::parse("HIGH"), Some(Severity::High));
+        assert_eq!(Severity::parse("moderate"), Some(Severity::Medium));
+        assert_eq!(Severity::parse("高危"), Some(Severity::High));
+        assert_eq!(Severity::parse("严重"), Some(Severity::Critical));
+        assert_eq!(Severity::parse("n/a"), None);
+        assert_eq!(Severity::from_score(6.5), Severity::Medium);
+        assert!(Severity::Critical > Severity::Low);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2023-02-07T20:15:17.107"), NaiveDate::from_ymd_opt(2023, 2, 7));
+        assert_eq!(parse_date("2023-02-07"), NaiveDate::from_ymd_opt(2023, 2, 7));
+        assert_eq!(parse_date(""), None);
+    }
+}
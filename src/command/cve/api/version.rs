@@ -0,0 +1,322 @@
+use std::cmp::Ordering;
+
+/// A version interval, unbounded on a side without a bound.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VersionRange {
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Bound {
+    version: String,
+    inclusive: bool,
+}
+
+impl Bound {
+    fn new(version: &str, inclusive: bool) -> Bound {
+        Bound {
+            version: version.trim().to_string(),
+            inclusive,
+        }
+    }
+}
+
+impl VersionRange {
+    /// Comma separated constraints like `>=2.0,<3.0`, a bare version matches only itself and
+    /// an empty string or `*` matches every version.
+    pub fn parse(s: &str) -> Result<VersionRange, String> {
+        let mut range = VersionRange::default();
+        for item in s.split(',').map(|x| x.trim()).filter(|x| !x.is_empty() && *x != "*") {
+            let (op, version) = match item.find(|c: char| c.is_ascii_alphanumeric()) {
+                Some(i) => item.split_at(i),
+                None => return Err(format!("invalid version range {}", s)),
+            };
+            match op.trim() {
+                ">=" => range.lower = Some(Bound::new(version, true)),
+                ">" => range.lower = Some(Bound::new(version, false)),
+                "<=" => range.upper = Some(Bound::new(version, true)),
+                "<" => range.upper = Some(Bound::new(version, false)),
+                "" | "=" | "==" => {
+                    range.lower = Some(Bound::new(version, true));
+                    range.upper = Some(Bound::new(version, true));
+                }
+                _ => return Err(format!("invalid version range {}", s)),
+            }
+        }
+        Ok(range)
+    }
+
+    /// The range column of the AVD affected software table, e.g.
+    /// `From (including) 2.3.0 Up to (including) 3.3.2`, or a single version.
+    pub fn parse_avd(s: &str) -> Option<VersionRange> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        if words.is_empty() {
+            return Some(VersionRange::default());
+        }
+        if words.len() == 1 {
+            return VersionRange::parse(words[0]).ok();
+        }
+        let mut range = VersionRange::default();
+        let mut i = 0;
+        while i < words.len() {
+            let (is_lower, skip) = match (words[i], words.get(i + 1)) {
+                ("From", _) => (true, 1),
+                ("Up", Some(&"to")) => (false, 2),
+                _ => return None,
+            };
+            let inclusive = match words.get(i + skip) {
+                Some(&"(including)") => true,
+                Some(&"(excluding)") => false,
+                _ => return None,
+            };
+            let bound = Bound::new(words.get(i + skip + 1)?, inclusive);
+            if is_lower {
+                range.lower = Some(bound);
+            } else {
+                range.upper = Some(bound);
+            }
+            i += skip + 2;
+        }
+        Some(range)
+    }
+
+    /// The range of one version.
+    pub fn exact(version: &str) -> VersionRange {
+        VersionRange {
+            lower: Some(Bound::new(version, true)),
+            upper: Some(Bound::new(version, true)),
+        }
+    }
+
+    /// `introduced` inclusive to `end`, where an empty `introduced` or `0` is unbounded.
+    pub fn from_events(introduced: &str, end: Option<(&str, bool)>) -> VersionRange {
+        let lower = match introduced.trim() {
+            "" | "0" => None,
+            v => Some(Bound::new(v, true)),
+        };
+        VersionRange {
+            lower,
+            upper: end.map(|(v, inclusive)| Bound::new(v, inclusive)),
+        }
+    }
+
+    pub fn contains(&self, version: &str) -> bool {
+        self.intersects(&VersionRange::exact(version))
+    }
+
+    pub fn is_any(&self) -> bool {
+        self.lower.is_none() && self.upper.is_none()
+    }
+
+    pub fn intersects(&self, other: &VersionRange) -> bool {
+        let lower = match (&self.lower, &other.lower) {
+            (Some(a), Some(b)) => Some(tighter(a, b, Ordering::Greater)),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        let upper = match (&self.upper, &other.upper) {
+            (Some(a), Some(b)) => Some(tighter(a, b, Ordering::Less)),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => match compare_version(&lower.version, &upper.version) {
+                Ordering::Less => true,
+                Ordering::Equal => lower.inclusive && upper.inclusive,
+                Ordering::Greater => false,
+            },
+            _ => true,
+        }
+    }
+}
+
+/// The bound of `a` and `b` ordered `toward`, exclusive if either is at the same version.
+fn tighter(a: &Bound, b: &Bound, toward: Ordering) -> Bound {
+    match compare_version(&a.version, &b.version) {
+        Ordering::Equal => Bound::new(&a.version, a.inclusive && b.inclusive),
+        ord if ord == toward => a.clone(),
+        _ => b.clone(),
+    }
+}
+
+/// Compares dotted versions part by part, numerically where both parts are numbers,
+/// so `2.10` > `2.9`, `2.0` == `2.0.0` and `v1.2` == `1.2`. A Debian epoch `N:` is compared
+/// first. A pre-release sorts below its release as in SemVer, PEP 440 and Debian, so
+/// `2.15.0-rc1`, `2.15.0rc1` and `2.15.0~rc1` are all < `2.15.0`.
+pub fn compare_version(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a) = split_epoch(a);
+    let (b_epoch, b) = split_epoch(b);
+    if a_epoch != b_epoch {
+        return a_epoch.cmp(&b_epoch);
+    }
+    let (a_release, a_pre) = split_pre_release(a);
+    let (b_release, b_pre) = split_pre_release(b);
+    match compare_parts(a_release, b_release) {
+        Ordering::Equal => match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(x), Some(y)) => compare_parts(x, y),
+        },
+        ord => ord,
+    }
+}
+
+/// Splits `1:2.0-1` into the epoch `1` and `2.0-1`, a version without an epoch has epoch 0.
+fn split_epoch(v: &str) -> (u64, &str) {
+    let v = v.trim();
+    match v.split_once(':') {
+        Some((epoch, rest)) if !epoch.is_empty() && epoch.bytes().all(|x| x.is_ascii_digit()) => {
+            (epoch.parse().unwrap_or(u64::MAX), rest)
+        }
+        _ => (0, v),
+    }
+}
+
+/// Splits `v1.2.3-rc1` into `1.2.3` and `rc1`. Only a suffix starting with a letter or with the
+/// timestamp of a Go pseudo-version is a pre-release, a Debian revision like `1.2-0+deb11u3`
+/// stays part of the release.
+fn split_pre_release(v: &str) -> (&str, Option<&str>) {
+    let v = v.trim();
+    let v = match v.strip_prefix(['v', 'V']) {
+        Some(x) if x.starts_with(|c: char| c.is_ascii_digit()) => x,
+        _ => v,
+    };
+    match v.split_once('-') {
+        Some((release, pre)) if is_pre_release(pre) => (release, Some(pre)),
+        _ => (v, None),
+    }
+}
+
+fn is_pre_release(s: &str) -> bool {
+    let first = s.split(['.', '-']).next().unwrap_or_default();
+    s.starts_with(|c: char| c.is_ascii_alphabetic())
+        || (first.len() == 14 && first.bytes().all(|x| x.is_ascii_digit()))
+}
+
+/// The PEP 440 pre-release words, which sort below the release when a number follows them.
+const PRE_RELEASE_WORDS: [&str; 9] = ["a", "alpha", "b", "beta", "c", "rc", "pre", "preview", "dev"];
+
+/// A run of a version part: `0rc1` is `0`, `rc`, `1`.
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// The Debian `~`, below everything, even the end of the version.
+    Tilde,
+    /// Digits without their leading zeros.
+    Number(&'a str),
+    /// Letters and the pre-release flag of a PEP 440 word followed by a number, e.g. `rc1`.
+    Word(&'a str, bool),
+}
+
+fn tokens(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for part in s.split(['.', '-', '_', '+']) {
+        let start = tokens.len();
+        let mut rest = part;
+        while let Some(c) = rest.chars().next() {
+            let len = match c {
+                '~' => 1,
+                _ if c.is_ascii_digit() => rest.find(|x: char| !x.is_ascii_digit()).unwrap_or(rest.len()),
+                _ => rest.find(|x: char| x.is_ascii_digit() || x == '~').unwrap_or(rest.len()),
+            };
+            let (run, tail) = rest.split_at(len);
+            tokens.push(match c {
+                '~' => Token::Tilde,
+                _ if c.is_ascii_digit() => Token::Number(run.trim_start_matches('0')),
+                _ => Token::Word(run, false),
+            });
+            rest = tail;
+        }
+        for i in start..tokens.len() {
+            if let (Token::Word(word, _), Some(Token::Number(_))) = (&tokens[i], tokens.get(i + 1)) {
+                let pre = PRE_RELEASE_WORDS.contains(&word.to_lowercase().as_str());
+                tokens[i] = Token::Word(word, pre);
+            }
+        }
+    }
+    tokens
+}
+
+/// Compares the runs of two versions, a missing run counts as `0`. A number is above a
+/// pre-release word and below any other word, so `2.0rc1` < `2.0` < `2.0n`.
+fn compare_parts(a: &str, b: &str) -> Ordering {
+    let (a, b) = (tokens(a), tokens(b));
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).unwrap_or(&Token::Number(""));
+        let y = b.get(i).unwrap_or(&Token::Number(""));
+        let ord = match (x, y) {
+            (Token::Tilde, Token::Tilde) => Ordering::Equal,
+            (Token::Tilde, _) => Ordering::Less,
+            (_, Token::Tilde) => Ordering::Greater,
+            (Token::Number(x), Token::Number(y)) => x.len().cmp(&y.len()).then_with(|| x.cmp(y)),
+            (Token::Number(_), Token::Word(_, pre)) => if *pre { Ordering::Greater } else { Ordering::Less },
+            (Token::Word(_, pre), Token::Number(_)) => if *pre { Ordering::Less } else { Ordering::Greater },
+            (Token::Word(x, _), Token::Word(y, _)) => x.cmp(y),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_version_range() {
+        assert_eq!(compare_version("2.10.0", "2.9"), Ordering::Greater);
+        assert_eq!(compare_version("2.0", "2.0.0"), Ordering::Equal);
+        // PEP 440 pre-releases, Debian tildes and epochs, letters after numbers
+        assert_eq!(compare_version("2.0.0rc1", "2.0.0"), Ordering::Less);
+        assert_eq!(compare_version("2.0.0rc1", "2.0.0rc2"), Ordering::Less);
+        assert_eq!(compare_version("1.0~rc1-1", "1.0-1"), Ordering::Less);
+        assert_eq!(compare_version("1.0~rc1", "1.0"), Ordering::Less);
+        assert_eq!(compare_version("1:1.0-1", "2.0-1"), Ordering::Greater);
+        assert_eq!(compare_version("1:2.0", "1:10.0"), Ordering::Less);
+        assert_eq!(compare_version("10", "9a"), Ordering::Greater);
+        assert_eq!(compare_version("1.1.1n", "1.1.1"), Ordering::Greater);
+        assert_eq!(compare_version("1.01", "1.1"), Ordering::Equal);
+        assert!(VersionRange::from_events("0", Some(("2.0.0", false))).contains("2.0.0rc1"));
+        assert!(!VersionRange::from_events("1:1.0-1", None).contains("2.0-1"));
+
+        let affected = VersionRange::parse_avd("From (including) 2.3.0 Up to (including) 3.3.2").unwrap();
+        assert!(affected.intersects(&VersionRange::parse("3.0.0").unwrap()));
+        assert!(affected.intersects(&VersionRange::parse("3.3.2").unwrap()));
+        assert!(!affected.intersects(&VersionRange::parse("3.4.0").unwrap()));
+        assert!(affected.intersects(&VersionRange::parse(">=3.3, <4").unwrap()));
+        assert!(!affected.intersects(&VersionRange::parse("<2.3.0").unwrap()));
+
+        let affected = VersionRange::parse_avd("Up to (excluding) 2.3.1").unwrap();
+        assert!(!affected.intersects(&VersionRange::parse(">=2.3.1").unwrap()));
+        assert!(VersionRange::parse("").unwrap().is_any());
+        assert!(VersionRange::parse("~>1.0").is_err());
+        assert_eq!(VersionRange::parse_avd("(including) 2.0"), None);
+    }
+
+    #[test]
+    fn test_contains() {
+        let range = VersionRange::from_events("2.0.0", Some(("2.15.0", false)));
+        assert!(range.contains("2.14.1"));
+        assert!(!range.contains("2.15.0"));
+        assert!(!range.contains("1.2"));
+        assert!(VersionRange::from_events("0", None).contains("1.0"));
+
+        // Go module versions carry a leading v, OSV ranges do not
+        assert!(VersionRange::from_events("0", Some(("1.7.4", false))).contains("v1.2.3"));
+        assert!(!VersionRange::from_events("0", Some(("1.7.4", false))).contains("v1.7.4"));
+        // a pre-release comes before its release
+        assert!(range.contains("2.15.0-rc1"));
+        assert!(!range.contains("2.0.0-beta1"));
+        assert_eq!(compare_version("2.15.0-rc1", "2.15.0-rc2"), Ordering::Less);
+        assert_eq!(
+            compare_version("v0.0.0-20220906165146-f3363e06e74c", "0.0.0-20220906165146-f3363e06e74c"),
+            Ordering::Equal
+        );
+        assert_eq!(compare_version("v0.0.0-20210101000000-abc", "0.0.0"), Ordering::Less);
+        // a Debian revision is not a pre-release
+        assert_eq!(compare_version("1.1.1n-0+deb11u3", "1.1.1n"), Ordering::Greater);
+        assert_eq!(compare_version("1.1.1n-0+deb11u3", "1.1.1n-0+deb11u4"), Ordering::Less);
+    }
+}
@@ -0,0 +1,248 @@
+use regex::Regex;
+use serde::{self, Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+
+/// A logical column of a scanner spreadsheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnField {
+    Component,
+    Version,
+    Object,
+    VulCount,
+    Cve,
+    BinaryObject,
+}
+
+/// How one logical column is found: an exact header (case insensitive), a header regex,
+/// or a fixed column letter that wins over the header.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FieldRule {
+    pub aliases: Vec<String>,
+    pub regex: Vec<String>,
+    /// e.g. `B` or `AA`.
+    pub column: Option<String>,
+}
+
+/// The header mapping of a scanner report, loaded from a TOML or YAML file like
+///
+/// ```toml
+/// [component]
+/// aliases = ["Package"]
+/// regex = ["(?i)^component( name)?$"]
+///
+/// [cve]
+/// column = "D"
+/// ```
+///
+/// Fields missing from the file keep the BDBA headers of the default profile.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ColumnProfile {
+    pub component: FieldRule,
+    pub version: FieldRule,
+    pub object: FieldRule,
+    pub vul_count: FieldRule,
+    pub cve: FieldRule,
+    pub binary_object: FieldRule,
+}
+
+impl Default for ColumnProfile {
+    /// The headers of the English and the Chinese BDBA report.
+    fn default() -> Self {
+        let aliases = |v: &[&str]| FieldRule {
+            aliases: v.iter().map(|x| x.to_string()).collect(),
+            ..FieldRule::default()
+        };
+        ColumnProfile {
+            component: aliases(&["Component", "组件名称"]),
+            version: aliases(&["Version", "组件版本"]),
+            object: aliases(&["Object full path", "文件路径"]),
+            vul_count: aliases(&["Vulnerability count", "漏洞数量"]),
+            cve: aliases(&["CVE", "CVE编号"]),
+            binary_object: aliases(&["Object", "文件名"]),
+        }
+    }
+}
+
+impl ColumnProfile {
+    /// Reads a `.yaml`/`.yml` file as YAML and anything else as TOML.
+    pub fn load(path: &str) -> Result<ColumnProfile, Box<dyn Error>> {
+        let data = fs::read_to_string(path)?;
+        if path.ends_with(".yaml") || path.ends_with(".yml") {
+            Ok(serde_yaml::from_str(&data)?)
+        } else {
+            Ok(toml::from_str(&data)?)
+        }
+    }
+
+    pub fn compile(&self) -> Result<ColumnMatcher, String> {
+        let rules = [
+            (ColumnField::Component, &self.component),
+            (ColumnField::Version, &self.version),
+            (ColumnField::Object, &self.object),
+            (ColumnField::VulCount, &self.vul_count),
+            (ColumnField::Cve, &self.cve),
+            (ColumnField::BinaryObject, &self.binary_object),
+        ];
+        let mut matcher = ColumnMatcher { rules: Vec::new() };
+        for (field, rule) in rules {
+            let mut regex = Vec::new();
+            for r in rule.regex.iter() {
+                regex.push(Regex::new(r).map_err(|e| format!("invalid regex of {:?}: {}", field, e))?);
+            }
+            let column = match &rule.column {
+                Some(v) => Some(column_index(v).ok_or(format!("invalid column of {:?}: {}", field, v))?),
+                None => None,
+            };
+            matcher.rules.push(CompiledRule {
+                field,
+                aliases: rule.aliases.iter().map(|x| x.trim().to_lowercase()).collect(),
+                regex,
+                column,
+            });
+        }
+        Ok(matcher)
+    }
+}
+
+struct CompiledRule {
+    field: ColumnField,
+    aliases: Vec<String>,
+    regex: Vec<Regex>,
+    column: Option<usize>,
+}
+
+impl CompiledRule {
+    fn is_match(&self, header: &str) -> bool {
+        let header = header.trim();
+        self.aliases.contains(&header.to_lowercase()) || self.regex.iter().any(|x| x.is_match(header))
+    }
+}
+
+/// A compiled `ColumnProfile`.
+pub struct ColumnMatcher {
+    rules: Vec<CompiledRule>,
+}
+
+impl ColumnMatcher {
+    /// The column of `field` in the header row: its fixed column, else the first header it matches.
+    pub fn locate(&self, headers: &[&str], field: ColumnField) -> Option<usize> {
+        let rule = self.rules.iter().find(|x| x.field == field)?;
+        if rule.column.is_some() {
+            return rule.column;
+        }
+        headers.iter().position(|x| rule.is_match(x))
+    }
+
+    /// How many of `fields` the header row has, a fixed column counts when the row reaches it.
+    pub fn score(&self, headers: &[&str], fields: &[ColumnField]) -> usize {
+        fields
+            .iter()
+            .filter(|x| matches!(self.locate(headers, **x), Some(c) if c < headers.len()))
+            .count()
+    }
+
+    /// The columns of every field, `None` if one is missing or two share a column.
+    pub fn locate_all(&self, headers: &[&str], fields: &[ColumnField]) -> Option<Vec<usize>> {
+        let mut columns: Vec<usize> = Vec::new();
+        for field in fields {
+            let column = self.locate(headers, *field)?;
+            if columns.contains(&column) {
+                return None;
+            }
+            columns.push(column);
+        }
+        Some(columns)
+    }
+}
+
+impl Default for ColumnMatcher {
+    fn default() -> Self {
+        ColumnProfile::default().compile().unwrap()
+    }
+}
+
+/// The zero based index of a column letter, e.g. `A` is 0 and `AA` is 26.
+pub fn column_index(letter: &str) -> Option<usize> {
+    let letter = letter.trim();
+    if letter.is_empty() {
+        return None;
+    }
+    let mut index = 0usize;
+    for c in letter.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        index = index * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1);
+    }
+    Some(index - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_default_profile() {
+        let matcher = ColumnMatcher::default();
+        let headers = ["Object", "Object full path", "Component", "Version", "Vulnerability count"];
+        let fields = [
+            ColumnField::Component,
+            ColumnField::Version,
+            ColumnField::Object,
+            ColumnField::VulCount,
+            ColumnField::BinaryObject,
+        ];
+        assert_eq!(matcher.locate_all(&headers, &fields), Some(vec![2, 3, 1, 4, 0]));
+
+        let headers = ["组件名称", "组件版本", "CVE编号", "文件路径"];
+        assert_eq!(matcher.locate(&headers, ColumnField::Cve), Some(2));
+        assert_eq!(matcher.locate(&headers, ColumnField::VulCount), None);
+        assert_eq!(matcher.locate_all(&headers, &fields), None);
+        assert_eq!(matcher.score(&headers, &fields), 3);
+    }
+
+    #[test]
+    fn test_load_profile() {
+        let profile: ColumnProfile = toml::from_str(
+            r#"
+            [component]
+            regex = ["(?i)^(package|component) name$"]
+            [cve]
+            column = "D"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(profile.version, ColumnProfile::default().version);
+        let matcher = profile.compile().unwrap();
+        let headers = ["Package Name", "Version", "Vulnerability", "Id"];
+        assert_eq!(matcher.locate(&headers, ColumnField::Component), Some(0));
+        assert_eq!(matcher.locate(&headers, ColumnField::Version), Some(1));
+        assert_eq!(matcher.locate(&headers, ColumnField::Cve), Some(3));
+
+        let profile: ColumnProfile = serde_yaml::from_str(
+            r#"
+            version:
+              aliases: ["Installed Version"]
+            "#,
+        )
+        .unwrap();
+        let matcher = profile.compile().unwrap();
+        assert_eq!(matcher.locate(&["Installed Version"], ColumnField::Version), Some(0));
+        assert_eq!(matcher.locate(&["Version"], ColumnField::Version), None);
+
+        let profile: ColumnProfile = toml::from_str("[cve]\nregex = [\"(\"]").unwrap();
+        assert!(profile.compile().is_err());
+    }
+
+    #[test]
+    fn test_column_index() {
+        assert_eq!(column_index("A"), Some(0));
+        assert_eq!(column_index("d"), Some(3));
+        assert_eq!(column_index("AA"), Some(26));
+        assert_eq!(column_index("1"), None);
+    }
+}
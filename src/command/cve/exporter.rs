@@ -2,16 +2,21 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use clap::ArgMatches;
+use encoding_rs::Encoding;
 use xlsxwriter::Workbook;
-use crate::command::cve::utils;
+use crate::command::cve::{utils, CVE_API};
+use crate::command::lib::text;
 use tokio;
 
 
 pub fn handler(matches: &ArgMatches) {
     let cve_input = matches.get_many::<String>("file").unwrap();
     let detail = matches.get_flag("detail");
+    let jobs = *matches.get_one::<usize>("jobs").unwrap();
     let path = matches.get_one::<String>("path").unwrap();
     let output = matches.get_one::<String>("output").unwrap();
+    let encoding = matches.get_one::<&'static Encoding>("encoding").copied();
+    super::register_providers(matches);
 
     for (_, file) in cve_input.clone().enumerate() {
         if !Path::exists(Path::new(file)) {
@@ -33,7 +38,7 @@ pub fn handler(matches: &ArgMatches) {
 
     let mut cve_ids: Vec<String> = Vec::new();
     for (_, file) in cve_input.enumerate() {
-        let contents = fs::read_to_string(file).expect("Couldn't find or load that file.");
+        let contents = text::read(file, encoding).expect("Couldn't find or load that file.");
         let ids: Vec<String> = serde_json::from_str(&contents).unwrap();
         cve_ids.extend(ids)
     }
@@ -46,5 +51,10 @@ pub fn handler(matches: &ArgMatches) {
         );
     }
 
-    tokio::runtime::Runtime::new().unwrap().block_on(utils::write_cve_output_async(&cve_map, &mut out, detail));
+    tokio::runtime::Runtime::new().unwrap().block_on(utils::write_cve_output_async(&cve_map, &mut out, detail, jobs));
+    if detail {
+        if let Some(stats) = CVE_API.lock().unwrap().cache_stats() {
+            println!("{}", stats);
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+use crate::command::lib::table::{from_rows, parser_delimited};
+use crate::command::lib::text;
+use calamine::{DataType, Range};
+use encoding_rs::Encoding;
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The component sheet headers of the English BDBA report, used for the tables of a JSON result.
+const COMPONENT_HEADERS: [&str; 5] = ["Component", "Version", "Object", "Object full path", "Vulnerability count"];
+/// The vulnerability sheet headers of the English BDBA report.
+const VULNERABILITY_HEADERS: [&str; 4] = ["Component", "Version", "Object full path", "CVE"];
+
+/// True for the BDBA exports of several tables: a directory or zip of the CSV bundle, or a JSON
+/// result of the REST API. A single sheet or CSV file is opened as a table.
+pub fn is_export(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    Path::new(path).is_dir() || [".zip", ".json"].iter().any(|x| lower.ends_with(x))
+}
+
+/// The tables of a BDBA export, every table is matched by its headers like a workbook sheet.
+pub fn load(
+    path: &str,
+    delimiter: Option<u8>,
+    encoding: Option<&'static Encoding>,
+) -> Result<Vec<Range<DataType>>, Box<dyn Error>> {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".json") {
+        return parser_json(&text::read(path, encoding)?);
+    }
+    let mut tables = Vec::new();
+    if lower.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            if !entry.name().to_lowercase().ends_with(".csv") {
+                continue;
+            }
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            tables.push(parser_delimited(&text::decode(&data, encoding), delimiter)?);
+        }
+    } else {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)?
+            .flatten()
+            .map(|x| x.path())
+            .filter(|x| x.to_string_lossy().to_lowercase().ends_with(".csv"))
+            .collect();
+        files.sort();
+        for file in files {
+            tables.push(parser_delimited(&text::read(&file.to_string_lossy(), encoding)?, delimiter)?);
+        }
+    }
+    if tables.is_empty() {
+        return Err(format!("no csv file in {}", path).into());
+    }
+    Ok(tables)
+}
+
+/// The `results` of `GET /api/product/<id>/` as a component and a vulnerability table,
+/// one row per component object and one per component object and vulnerability.
+pub fn parser_json(data: &str) -> Result<Vec<Range<DataType>>, Box<dyn Error>> {
+    let root: Value = serde_json::from_str(data)?;
+    let results = root.get("results").unwrap_or(&root);
+    let components = match results["components"].as_array() {
+        Some(v) => v,
+        None => return Err("not a BDBA result".into()),
+    };
+    let mut component_rows = vec![COMPONENT_HEADERS.iter().map(|x| x.to_string()).collect()];
+    let mut vulnerability_rows = vec![VULNERABILITY_HEADERS.iter().map(|x| x.to_string()).collect()];
+    for c in components {
+        let name = c["lib"].as_str().unwrap_or_default();
+        let version = c["version"].as_str().unwrap_or_default();
+        let mut cves: Vec<&str> = Vec::new();
+        for v in c["vulns"].as_array().into_iter().flatten() {
+            let cve = v["vuln"]["cve"].as_str().unwrap_or_default();
+            if !cve.is_empty() && !cves.contains(&cve) {
+                cves.push(cve);
+            }
+        }
+        let mut objects: Vec<(String, String)> = c["extended-objects"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|x| {
+                let full_path: Vec<&str> = x["fullpath"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|x| x.as_str())
+                    .collect();
+                (x["name"].as_str().unwrap_or_default().to_string(), full_path.join("/"))
+            })
+            .collect();
+        if objects.is_empty() {
+            objects.push((String::new(), String::new()));
+        }
+        for (object, full_path) in objects {
+            component_rows.push(vec![
+                name.to_string(),
+                version.to_string(),
+                object,
+                full_path.clone(),
+                cves.len().to_string(),
+            ]);
+            for cve in cves.iter() {
+                vulnerability_rows.push(vec![
+                    name.to_string(),
+                    version.to_string(),
+                    full_path.clone(),
+                    cve.to_string(),
+                ]);
+            }
+        }
+    }
+    Ok(vec![from_rows(component_rows), from_rows(vulnerability_rows)])
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const BDBA_JSON_STR: &str = r#"{
+  "meta": {"code": 200},
+  "results": {
+    "product_id": 42,
+    "filename": "app.tar",
+    "components": [
+      {
+        "lib": "openssl",
+        "version": "1.0.2k",
+        "vulns": [
+          {"exact": true, "vuln": {"cve": "CVE-2016-2105", "cvss": 5.0}},
+          {"exact": true, "vuln": {"cve": "CVE-2016-2106", "cvss": 5.0}}
+        ],
+        "extended-objects": [
+          {"name": "libssl.so.1.0.2k", "fullpath": ["app.tar", "layer.tar", "usr/lib64/libssl.so.1.0.2k"]}
+        ]
+      },
+      {"lib": "zlib", "version": "1.2.13", "vulns": [], "extended-objects": []}
+    ]
+  }
+}"#;
+
+    #[test]
+    fn test_parser_json() {
+        let tables = parser_json(BDBA_JSON_STR).unwrap();
+        assert_eq!(tables[0].get_size(), (3, 5));
+        assert_eq!(
+            tables[0].get_value((1, 3)),
+            Some(&DataType::String(String::from("app.tar/layer.tar/usr/lib64/libssl.so.1.0.2k")))
+        );
+        assert_eq!(tables[0].get_value((1, 4)), Some(&DataType::String(String::from("2"))));
+        assert_eq!(tables[0].get_value((2, 4)), Some(&DataType::String(String::from("0"))));
+        assert_eq!(tables[1].get_size(), (3, 4));
+        assert_eq!(tables[1].get_value((2, 3)), Some(&DataType::String(String::from("CVE-2016-2106"))));
+        assert!(parser_json(r#"{"bomFormat": "CycloneDX"}"#).is_err());
+    }
+
+    #[test]
+    fn test_load_zip() {
+        let dir = std::env::temp_dir().join("etool-bdba-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        for (name, data) in [
+            // a blank count is left empty, which the component parser reads as 0
+            ("components.csv", "Component,Version,Vulnerability count\nopenssl,1.0.2k,2\nzlib,1.2.11,\n"),
+            ("vulnerabilities.csv", "Component,Version,CVE\nopenssl,1.0.2k,CVE-2016-2105\n"),
+            ("README.txt", "not a table"),
+        ] {
+            writer.start_file(name, zip::write::FileOptions::default()).unwrap();
+            std::io::Write::write_all(&mut writer, data.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert!(is_export(path.to_str().unwrap()));
+        assert!(!is_export("Open_Source_Binary_Result.xlsx"));
+        let tables = load(path.to_str().unwrap(), None, None).unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].get_size(), (3, 3));
+        assert_eq!(tables[0].get_value((1, 2)), Some(&DataType::String(String::from("2"))));
+        assert_eq!(tables[0].get_value((2, 2)), Some(&DataType::Empty));
+        assert_eq!(tables[1].get_value((1, 2)), Some(&DataType::String(String::from("CVE-2016-2105"))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,396 @@
+use crate::command::cve::format::Finding;
+use crate::command::lib::text;
+use encoding_rs::Encoding;
+use roxmltree::Node;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+/// A CycloneDX 1.4 - 1.6 BOM, read from JSON or XML into the parts the findings need.
+#[derive(Debug, Default)]
+struct Bom {
+    /// `name:version` of the metadata component, e.g. the scanned image.
+    object: String,
+    /// Every component in document order, nested ones after their parent.
+    components: Vec<Component>,
+    /// bom-ref -> index in `components`.
+    refs: HashMap<String, usize>,
+    vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Component {
+    name: String,
+    version: String,
+    purl: String,
+    cpe: String,
+    /// The first evidence occurrence or `syft:location:0:path` property.
+    path: String,
+}
+
+#[derive(Debug, Default)]
+struct Vulnerability {
+    id: String,
+    refs: Vec<String>,
+    /// The VEX `analysis.state`, e.g. `not_affected`.
+    state: String,
+}
+
+pub fn load(path: &str, encoding: Option<&'static Encoding>) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let data = text::read(path, encoding)?;
+    if data.trim_start().starts_with('<') {
+        parser_xml(&data)
+    } else {
+        parser_json(&data)
+    }
+}
+
+pub fn parser_json(data: &str) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let root: Value = serde_json::from_str(data)?;
+    if root["bomFormat"] != "CycloneDX" {
+        return Err("not a CycloneDX document".into());
+    }
+    let mut bom = Bom::default();
+    let metadata = &root["metadata"]["component"];
+    bom.object = object_name(json_str(metadata, "name"), json_str(metadata, "version"));
+    json_components(&root["components"], &mut bom);
+    for v in root["vulnerabilities"].as_array().into_iter().flatten() {
+        bom.vulnerabilities.push(Vulnerability {
+            id: json_str(v, "id").to_string(),
+            refs: v["affects"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|x| json_str(x, "ref").to_string())
+                .collect(),
+            state: json_str(&v["analysis"], "state").to_string(),
+        });
+    }
+    Ok(bom.findings())
+}
+
+fn json_str<'a>(v: &'a Value, key: &str) -> &'a str {
+    v[key].as_str().unwrap_or_default()
+}
+
+fn json_components(v: &Value, bom: &mut Bom) {
+    for c in v.as_array().into_iter().flatten() {
+        let mut path = c["evidence"]["occurrences"][0]["location"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        if path.is_empty() {
+            path = c["properties"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|x| x["name"] == "syft:location:0:path")
+                .map(|x| json_str(x, "value").to_string())
+                .unwrap_or_default();
+        }
+        bom.insert(
+            json_str(c, "bom-ref"),
+            Component {
+                name: json_str(c, "name").to_string(),
+                version: json_str(c, "version").to_string(),
+                purl: json_str(c, "purl").to_string(),
+                cpe: json_str(c, "cpe").to_string(),
+                path,
+            },
+        );
+        json_components(&c["components"], bom);
+    }
+}
+
+pub fn parser_xml(data: &str) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let document = roxmltree::Document::parse(data)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "bom" {
+        return Err("not a CycloneDX document".into());
+    }
+    let mut bom = Bom::default();
+    if let Some(metadata) = child(root, "metadata").and_then(|x| child(x, "component")) {
+        bom.object = object_name(&child_text(metadata, "name"), &child_text(metadata, "version"));
+    }
+    if let Some(components) = child(root, "components") {
+        xml_components(components, &mut bom);
+    }
+    if let Some(vulnerabilities) = child(root, "vulnerabilities") {
+        for v in vulnerabilities.children().filter(|x| x.has_tag_name("vulnerability")) {
+            let refs = match child(v, "affects") {
+                Some(affects) => affects
+                    .children()
+                    .filter(|x| x.has_tag_name("target"))
+                    .map(|x| child_text(x, "ref"))
+                    .collect(),
+                None => Vec::new(),
+            };
+            bom.vulnerabilities.push(Vulnerability {
+                id: child_text(v, "id"),
+                refs,
+                state: child(v, "analysis").map(|x| child_text(x, "state")).unwrap_or_default(),
+            });
+        }
+    }
+    Ok(bom.findings())
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|x| x.tag_name().name() == name)
+}
+
+fn child_text(node: Node, name: &str) -> String {
+    child(node, name)
+        .and_then(|x| x.text())
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn xml_components(node: Node, bom: &mut Bom) {
+    for c in node.children().filter(|x| x.tag_name().name() == "component") {
+        let mut path = child(c, "evidence")
+            .and_then(|x| child(x, "occurrences"))
+            .and_then(|x| child(x, "occurrence"))
+            .map(|x| child_text(x, "location"))
+            .unwrap_or_default();
+        if path.is_empty() {
+            path = child(c, "properties")
+                .and_then(|x| {
+                    x.children()
+                        .find(|p| p.attribute("name") == Some("syft:location:0:path"))
+                })
+                .and_then(|x| x.text())
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+        }
+        bom.insert(
+            c.attribute("bom-ref").unwrap_or_default(),
+            Component {
+                name: child_text(c, "name"),
+                version: child_text(c, "version"),
+                purl: child_text(c, "purl"),
+                cpe: child_text(c, "cpe"),
+                path,
+            },
+        );
+        if let Some(nested) = child(c, "components") {
+            xml_components(nested, bom);
+        }
+    }
+}
+
+fn object_name(name: &str, version: &str) -> String {
+    if version.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}:{}", name, version)
+    }
+}
+
+impl Bom {
+    /// Adds a component, one without a bom-ref cannot be affected but is still listed.
+    fn insert(&mut self, bom_ref: &str, component: Component) {
+        if !bom_ref.is_empty() {
+            self.refs.insert(bom_ref.to_string(), self.components.len());
+        }
+        self.components.push(component);
+    }
+
+    /// The component index of an `affects` ref, a BOM-Link like `urn:cdx:<serial>/1#<bom-ref>`
+    /// points at the bom-ref after its `#`, other refs such as purls with a `#subpath` are kept whole.
+    fn component(&self, r: &str) -> Option<usize> {
+        if let Some(i) = self.refs.get(r) {
+            return Some(*i);
+        }
+        match r.strip_prefix("urn:cdx:").and_then(|x| x.split_once('#')) {
+            Some((_, bom_ref)) => self.refs.get(bom_ref).copied(),
+            None => None,
+        }
+    }
+
+    /// One finding per vulnerability and affected component, refs to unknown components are
+    /// skipped. A component no vulnerability affects gets one finding without a cve, like an
+    /// SPDX package, so that `resolve_packages` can look it up.
+    fn findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut affected: HashSet<usize> = HashSet::new();
+        for v in self.vulnerabilities.iter() {
+            for r in v.refs.iter() {
+                let component = match self.component(r) {
+                    Some(i) => {
+                        affected.insert(i);
+                        &self.components[i]
+                    }
+                    None => {
+                        println!("{} affects unknown component {}", v.id, r);
+                        continue;
+                    }
+                };
+                findings.push(Finding {
+                    object: self.object.clone(),
+                    component: component.name.clone(),
+                    version: component.version.clone(),
+                    path: component.path.clone(),
+                    cve: v.id.clone(),
+                    purl: component.purl.clone(),
+                    cpe: component.cpe.clone(),
+                    vex: v.state.clone(),
+                });
+            }
+        }
+        for (i, component) in self.components.iter().enumerate() {
+            if affected.contains(&i) {
+                continue;
+            }
+            findings.push(Finding {
+                object: self.object.clone(),
+                component: component.name.clone(),
+                version: component.version.clone(),
+                path: component.path.clone(),
+                purl: component.purl.clone(),
+                cpe: component.cpe.clone(),
+                ..Finding::default()
+            });
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const CYCLONEDX_JSON_STR: &str = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.5",
+  "version": 1,
+  "metadata": {
+    "component": {"type": "container", "name": "registry.example.com/app", "version": "1.0"}
+  },
+  "components": [
+    {
+      "bom-ref": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1",
+      "type": "library",
+      "name": "log4j-core",
+      "version": "2.14.1",
+      "purl": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1",
+      "evidence": {"occurrences": [{"location": "/opt/app/lib/log4j-core-2.14.1.jar"}]}
+    },
+    {
+      "bom-ref": "pkg:golang/github.com/example/mod@v1.0.0#cmd/tool",
+      "type": "library",
+      "name": "github.com/example/mod",
+      "version": "v1.0.0"
+    },
+    {
+      "bom-ref": "app-jar",
+      "type": "application",
+      "name": "app",
+      "version": "1.0",
+      "components": [
+        {
+          "bom-ref": "jackson-databind",
+          "type": "library",
+          "name": "jackson-databind",
+          "version": "2.9.10",
+          "properties": [{"name": "syft:location:0:path", "value": "/opt/app/app.jar"}]
+        }
+      ]
+    },
+    {"type": "library", "name": "zlib", "version": "1.2.13-r0", "purl": "pkg:apk/alpine/zlib@1.2.13-r0?distro=alpine-3.18.4"}
+  ],
+  "vulnerabilities": [
+    {
+      "id": "CVE-2021-44228",
+      "affects": [{"ref": "urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79/1#pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"}],
+      "analysis": {"state": "exploitable"}
+    },
+    {
+      "id": "CVE-2020-36518",
+      "affects": [{"ref": "jackson-databind"}],
+      "analysis": {"state": "not_affected", "justification": "code_not_reachable"}
+    },
+    {
+      "id": "CVE-2023-0002",
+      "affects": [{"ref": "pkg:golang/github.com/example/mod@v1.0.0#cmd/tool"}]
+    },
+    {
+      "id": "CVE-2022-0001",
+      "affects": [{"ref": "missing"}, {"ref": "urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79/1#missing"}]
+    }
+  ]
+}"#;
+
+    const CYCLONEDX_XML_STR: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<bom xmlns="http://cyclonedx.org/schema/bom/1.4" version="1">
+  <metadata>
+    <component type="container">
+      <name>registry.example.com/app</name>
+      <version>1.0</version>
+    </component>
+  </metadata>
+  <components>
+    <component type="library" bom-ref="log4j-core">
+      <name>log4j-core</name>
+      <version>2.14.1</version>
+      <purl>pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1</purl>
+      <properties>
+        <property name="syft:location:0:path">/opt/app/lib/log4j-core-2.14.1.jar</property>
+      </properties>
+    </component>
+  </components>
+  <vulnerabilities>
+    <vulnerability bom-ref="v1">
+      <id>CVE-2021-44228</id>
+      <analysis>
+        <state>in_triage</state>
+      </analysis>
+      <affects>
+        <target><ref>log4j-core</ref></target>
+      </affects>
+    </vulnerability>
+  </vulnerabilities>
+</bom>"#;
+
+    #[test]
+    fn test_parser_json() {
+        let findings = parser_json(CYCLONEDX_JSON_STR).unwrap();
+        assert_eq!(findings.len(), 5);
+        assert_eq!(findings[0].object, "registry.example.com/app:1.0");
+        assert_eq!(findings[0].component_id(), "log4j-core2.14.1");
+        assert_eq!(findings[0].path, "/opt/app/lib/log4j-core-2.14.1.jar");
+        assert_eq!(findings[0].vex, "exploitable");
+        assert_eq!(findings[1].component, "jackson-databind");
+        assert_eq!(findings[1].path, "/opt/app/app.jar");
+        assert_eq!(findings[1].vex, "not_affected");
+        assert_eq!(findings[2].component, "github.com/example/mod");
+        assert_eq!(findings[2].cve, "CVE-2023-0002");
+        // components no vulnerability affects are listed without a cve
+        assert_eq!(findings[3].component_id(), "app1.0");
+        assert_eq!(findings[4].component, "zlib");
+        assert_eq!(findings[4].purl, "pkg:apk/alpine/zlib@1.2.13-r0?distro=alpine-3.18.4");
+        assert!(findings[3].cve.is_empty() && findings[4].cve.is_empty());
+        assert!(parser_json(r#"{"bomFormat": "SPDX"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parser_xml() {
+        let findings = parser_xml(CYCLONEDX_XML_STR).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].object, "registry.example.com/app:1.0");
+        assert_eq!(findings[0].cve, "CVE-2021-44228");
+        assert_eq!(findings[0].purl, "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1");
+        assert_eq!(findings[0].path, "/opt/app/lib/log4j-core-2.14.1.jar");
+        assert_eq!(findings[0].vex, "in_triage");
+
+        // an SBOM without vulnerabilities still lists its components
+        let sbom = CYCLONEDX_XML_STR.split("  <vulnerabilities>").next().unwrap().to_string() + "</bom>";
+        let findings = parser_xml(&sbom).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].component_id(), "log4j-core2.14.1");
+        assert!(findings[0].cve.is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::command::cve::format::Finding;
+use crate::command::lib::text;
+use encoding_rs::Encoding;
+use serde::{self, Deserialize};
+use serde_json::Value;
+use std::error::Error;
+
+/// A `grype -o json` report.
+#[derive(Debug, Deserialize)]
+struct GrypeReport {
+    #[serde(default)]
+    matches: Vec<GrypeMatch>,
+    #[serde(default)]
+    source: Option<GrypeSource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GrypeMatch {
+    vulnerability: GrypeVulnerability,
+    #[serde(default)]
+    related_vulnerabilities: Vec<GrypeVulnerability>,
+    artifact: GrypeArtifact,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeVulnerability {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeArtifact {
+    name: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    locations: Vec<GrypeLocation>,
+    #[serde(default)]
+    purl: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeLocation {
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeSource {
+    /// An object with the `userInput` image name for images, the scanned path for directories.
+    #[serde(default)]
+    target: Value,
+}
+
+impl GrypeSource {
+    fn target(&self) -> String {
+        match &self.target {
+            Value::String(v) => v.clone(),
+            Value::Object(v) => v
+                .get("userInput")
+                .and_then(|x| x.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+pub fn load(path: &str, encoding: Option<&'static Encoding>) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let data = text::read(path, encoding)?;
+    parser_json(&data)
+}
+
+/// One finding per match and artifact location. Matches on a GHSA or distro advisory are
+/// reported under their related CVE, the id the providers know.
+pub fn parser_json(data: &str) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let report: GrypeReport = serde_json::from_str(data)?;
+    let object = report.source.map(|x| x.target()).unwrap_or_default();
+    let mut findings = Vec::new();
+    for m in report.matches {
+        let cve = if m.vulnerability.id.starts_with("CVE-") {
+            m.vulnerability.id
+        } else {
+            m.related_vulnerabilities
+                .into_iter()
+                .map(|x| x.id)
+                .find(|x| x.starts_with("CVE-"))
+                .unwrap_or(m.vulnerability.id)
+        };
+        let mut paths: Vec<String> = m.artifact.locations.into_iter().map(|x| x.path).collect();
+        if paths.is_empty() {
+            paths.push(String::new());
+        }
+        for path in paths {
+            findings.push(Finding {
+                object: object.clone(),
+                component: m.artifact.name.clone(),
+                version: m.artifact.version.clone(),
+                path,
+                cve: cve.clone(),
+                purl: m.artifact.purl.clone(),
+                ..Finding::default()
+            });
+        }
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const GRYPE_JSON_STR: &str = r#"{
+  "matches": [
+    {
+      "vulnerability": {
+        "id": "GHSA-jfh8-c2jp-5v3q",
+        "dataSource": "https://github.com/advisories/GHSA-jfh8-c2jp-5v3q",
+        "severity": "Critical"
+      },
+      "relatedVulnerabilities": [
+        {"id": "CVE-2021-44228", "dataSource": "https://nvd.nist.gov/vuln/detail/CVE-2021-44228"}
+      ],
+      "artifact": {
+        "id": "5a2b6c1d",
+        "name": "log4j-core",
+        "version": "2.14.1",
+        "type": "java-archive",
+        "locations": [
+          {"path": "/opt/app/lib/log4j-core-2.14.1.jar", "layerID": "sha256:1b2c"},
+          {"path": "/opt/tools/log4j-core-2.14.1.jar", "layerID": "sha256:3d4e"}
+        ],
+        "language": "java",
+        "purl": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+      }
+    },
+    {
+      "vulnerability": {"id": "CVE-2019-1549", "severity": "Medium"},
+      "relatedVulnerabilities": [],
+      "artifact": {
+        "name": "libcrypto1.1",
+        "version": "1.1.1c-r0",
+        "type": "apk",
+        "locations": [{"path": "/lib/apk/db/installed"}],
+        "purl": "pkg:apk/alpine/libcrypto1.1@1.1.1c-r0?arch=x86_64"
+      }
+    }
+  ],
+  "source": {
+    "type": "image",
+    "target": {"userInput": "registry.example.com/app:1.0", "imageID": "sha256:9f8e"}
+  },
+  "distro": {"name": "alpine", "version": "3.10.2"}
+}"#;
+
+    #[test]
+    fn test_parser_json() {
+        let findings = parser_json(GRYPE_JSON_STR).unwrap();
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].cve, "CVE-2021-44228");
+        assert_eq!(findings[0].object, "registry.example.com/app:1.0");
+        assert_eq!(
+            findings[1].object_path(),
+            "registry.example.com/app:1.0/opt/tools/log4j-core-2.14.1.jar"
+        );
+        assert_eq!(findings[1].purl, "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1");
+        assert_eq!(findings[2].component_id(), "libcrypto1.11.1.1c-r0");
+
+        let findings = parser_json(r#"{"matches": [], "source": {"type": "directory", "target": "./app"}}"#).unwrap();
+        assert!(findings.is_empty());
+    }
+}
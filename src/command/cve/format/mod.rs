@@ -0,0 +1,101 @@
+use encoding_rs::Encoding;
+use std::error::Error;
+
+pub mod bdba;
+pub mod cyclonedx;
+pub mod grype;
+pub mod spdx;
+pub mod trivy;
+
+/// The report formats of `cve analyze`, BDBA reports are read as sheets by `analyze` itself.
+pub const FORMAT_BDBA: &str = "bdba";
+pub const FORMAT_TRIVY: &str = "trivy";
+pub const FORMAT_GRYPE: &str = "grype";
+pub const FORMAT_CYCLONEDX: &str = "cyclonedx";
+/// SBOMs without vulnerabilities, resolved against the local OSV/GHSA providers.
+pub const FORMAT_SPDX: &str = "spdx";
+
+/// One vulnerable component of a scanner report, the unit every importer produces.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Finding {
+    /// The scanned artifact, e.g. an image name, used as the image sheet key.
+    pub object: String,
+    pub component: String,
+    pub version: String,
+    /// The file of `object` the component was found in.
+    pub path: String,
+    /// A CVE or another advisory id, e.g. GHSA, empty for a component of an SBOM.
+    pub cve: String,
+    /// The package URL of the component, empty when the report has none.
+    pub purl: String,
+    /// The CPE 2.3 name of the component, empty when the report has none.
+    pub cpe: String,
+    /// The VEX analysis state of the finding, e.g. `not_affected`, empty without VEX data.
+    pub vex: String,
+}
+
+impl Finding {
+    /// The component key of the component sheet, the same `name` + `version` key BDBA rows get.
+    pub fn component_id(&self) -> String {
+        format!("{}{}", self.component, self.version)
+    }
+
+    /// True for the VEX states saying the component is not vulnerable.
+    pub fn is_not_affected(&self) -> bool {
+        self.vex == "not_affected" || self.vex == "false_positive"
+    }
+
+    /// The object path of the finding, matching the `Object full path` of BDBA rows.
+    pub fn object_path(&self) -> String {
+        format!("{}/{}", self.object, self.path.trim_start_matches('/'))
+    }
+}
+
+/// The page of an advisory id, AVD for CVEs like the BDBA input.
+pub fn advisory_link(id: &str) -> String {
+    if id.starts_with("GHSA-") {
+        format!("https://github.com/advisories/{}", id)
+    } else if id.starts_with("CVE-") {
+        format!("https://avd.aliyun.com/detail?id={}", id)
+    } else {
+        format!("https://osv.dev/vulnerability/{}", id)
+    }
+}
+
+/// The findings of a non BDBA report.
+pub fn load(format: &str, path: &str, encoding: Option<&'static Encoding>) -> Result<Vec<Finding>, Box<dyn Error>> {
+    match format {
+        FORMAT_TRIVY => trivy::load(path, encoding),
+        FORMAT_GRYPE => grype::load(path, encoding),
+        FORMAT_CYCLONEDX => cyclonedx::load(path, encoding),
+        FORMAT_SPDX => spdx::load(path, encoding),
+        _ => Err(format!("unsupported format {}", format).into()),
+    }
+}
+
+/// Expands the SBOM findings without a cve into one finding per id `query` returns for them,
+/// a component without any is kept once so it still appears in the inventory.
+pub fn resolve_packages<F>(findings: Vec<Finding>, query: F) -> Vec<Finding>
+where
+    F: Fn(&Finding) -> Vec<String>,
+{
+    let mut result = Vec::new();
+    for finding in findings {
+        if !finding.cve.is_empty() {
+            result.push(finding);
+            continue;
+        }
+        let ids = query(&finding);
+        if ids.is_empty() {
+            result.push(finding);
+            continue;
+        }
+        for id in ids {
+            result.push(Finding {
+                cve: id,
+                ..finding.clone()
+            });
+        }
+    }
+    result
+}
@@ -0,0 +1,290 @@
+use crate::command::cve::format::Finding;
+use crate::command::lib::text;
+use encoding_rs::Encoding;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// An SPDX 2.3 document, read from JSON or tag-value into the parts the findings need.
+#[derive(Debug, Default)]
+struct Document {
+    name: String,
+    /// The SPDXIDs the document describes, e.g. the scanned image.
+    describes: Vec<String>,
+    packages: Vec<Package>,
+    /// SPDXID -> file name.
+    files: HashMap<String, String>,
+    /// (element, relationship type, related element)
+    relationships: Vec<(String, String, String)>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Package {
+    id: String,
+    name: String,
+    version: String,
+    purl: String,
+    cpe: String,
+}
+
+pub fn load(path: &str, encoding: Option<&'static Encoding>) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let data = text::read(path, encoding)?;
+    if data.trim_start().starts_with('{') {
+        parser_json(&data)
+    } else {
+        parser_tag_value(&data)
+    }
+}
+
+pub fn parser_json(data: &str) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let root: Value = serde_json::from_str(data)?;
+    if !json_str(&root, "spdxVersion").starts_with("SPDX-") {
+        return Err("not an SPDX document".into());
+    }
+    let mut doc = Document {
+        name: json_str(&root, "name").to_string(),
+        ..Document::default()
+    };
+    for id in root["documentDescribes"].as_array().into_iter().flatten() {
+        doc.describes.push(id.as_str().unwrap_or_default().to_string());
+    }
+    for p in root["packages"].as_array().into_iter().flatten() {
+        let mut package = Package {
+            id: json_str(p, "SPDXID").to_string(),
+            name: json_str(p, "name").to_string(),
+            version: json_str(p, "versionInfo").to_string(),
+            ..Package::default()
+        };
+        for r in p["externalRefs"].as_array().into_iter().flatten() {
+            package.external_ref(json_str(r, "referenceType"), json_str(r, "referenceLocator"));
+        }
+        doc.packages.push(package);
+    }
+    for f in root["files"].as_array().into_iter().flatten() {
+        doc.files
+            .insert(json_str(f, "SPDXID").to_string(), json_str(f, "fileName").to_string());
+    }
+    for r in root["relationships"].as_array().into_iter().flatten() {
+        doc.relationship(
+            json_str(r, "spdxElementId"),
+            json_str(r, "relationshipType"),
+            json_str(r, "relatedSpdxElement"),
+        );
+    }
+    Ok(doc.findings())
+}
+
+fn json_str<'a>(v: &'a Value, key: &str) -> &'a str {
+    v[key].as_str().unwrap_or_default()
+}
+
+/// Reads `Tag: value` lines, an `SPDXID` belongs to the last `PackageName` or `FileName`.
+pub fn parser_tag_value(data: &str) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let mut doc = Document::default();
+    let mut is_spdx = false;
+    let mut file_name: Option<String> = None;
+    let mut in_text = false;
+    for line in data.lines() {
+        // multi-line values are wrapped in <text>...</text>
+        if in_text {
+            in_text = !line.contains("</text>");
+            continue;
+        }
+        let (tag, value) = match line.split_once(':') {
+            Some((tag, value)) => (tag.trim(), value.trim()),
+            None => continue,
+        };
+        if value.starts_with("<text>") && !value.contains("</text>") {
+            in_text = true;
+            continue;
+        }
+        match tag {
+            "SPDXVersion" => is_spdx = value.starts_with("SPDX-"),
+            "DocumentName" => doc.name = value.to_string(),
+            "PackageName" => {
+                file_name = None;
+                doc.packages.push(Package {
+                    name: value.to_string(),
+                    ..Package::default()
+                });
+            }
+            "FileName" => file_name = Some(value.to_string()),
+            "SPDXID" => match (&file_name, doc.packages.last_mut()) {
+                (Some(name), _) => {
+                    doc.files.insert(value.to_string(), name.clone());
+                }
+                (None, Some(package)) => package.id = value.to_string(),
+                (None, None) => {}
+            },
+            "PackageVersion" => {
+                if let Some(package) = doc.packages.last_mut() {
+                    package.version = value.to_string();
+                }
+            }
+            "ExternalRef" => {
+                let words: Vec<&str> = value.split_whitespace().collect();
+                if let (Some(package), [_, ref_type, locator, ..]) = (doc.packages.last_mut(), words.as_slice()) {
+                    package.external_ref(ref_type, locator);
+                }
+            }
+            "Relationship" => {
+                if let [element, relationship, related] = value.split_whitespace().collect::<Vec<&str>>()[..] {
+                    doc.relationship(element, relationship, related);
+                }
+            }
+            _ => {}
+        }
+    }
+    if !is_spdx {
+        return Err("not an SPDX document".into());
+    }
+    Ok(doc.findings())
+}
+
+impl Package {
+    fn external_ref(&mut self, ref_type: &str, locator: &str) {
+        match ref_type {
+            "purl" if self.purl.is_empty() => self.purl = locator.to_string(),
+            "cpe23Type" if self.cpe.is_empty() => self.cpe = locator.to_string(),
+            _ => {}
+        }
+    }
+}
+
+impl Document {
+    fn relationship(&mut self, element: &str, relationship: &str, related: &str) {
+        if element == "SPDXRef-DOCUMENT" && relationship == "DESCRIBES" {
+            if !self.describes.iter().any(|x| x == related) {
+                self.describes.push(related.to_string());
+            }
+        } else {
+            self.relationships
+                .push((element.to_string(), relationship.to_string(), related.to_string()));
+        }
+    }
+
+    /// The name of the first file related to the package `id`, in either direction.
+    fn package_path(&self, id: &str) -> String {
+        self.relationships
+            .iter()
+            .find_map(|(a, _, b)| {
+                if a == id {
+                    self.files.get(b)
+                } else if b == id {
+                    self.files.get(a)
+                } else {
+                    None
+                }
+            })
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// One finding without a cve per package, the described package names the object.
+    fn findings(&self) -> Vec<Finding> {
+        let object = self
+            .packages
+            .iter()
+            .find(|x| self.describes.contains(&x.id))
+            .map(|x| {
+                if x.version.is_empty() {
+                    x.name.clone()
+                } else {
+                    format!("{}:{}", x.name, x.version)
+                }
+            })
+            .unwrap_or_else(|| self.name.clone());
+        self.packages
+            .iter()
+            .filter(|x| !self.describes.contains(&x.id))
+            .map(|x| Finding {
+                object: object.clone(),
+                component: x.name.clone(),
+                version: x.version.clone(),
+                path: self.package_path(&x.id),
+                purl: x.purl.clone(),
+                cpe: x.cpe.clone(),
+                ..Finding::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const SPDX_JSON_STR: &str = r#"{
+  "spdxVersion": "SPDX-2.3",
+  "dataLicense": "CC0-1.0",
+  "SPDXID": "SPDXRef-DOCUMENT",
+  "name": "app-sbom",
+  "documentDescribes": ["SPDXRef-image"],
+  "packages": [
+    {"SPDXID": "SPDXRef-image", "name": "registry.example.com/app", "versionInfo": "1.0"},
+    {
+      "SPDXID": "SPDXRef-log4j",
+      "name": "log4j-core",
+      "versionInfo": "2.14.1",
+      "externalRefs": [
+        {"referenceCategory": "SECURITY", "referenceType": "cpe23Type", "referenceLocator": "cpe:2.3:a:apache:log4j:2.14.1:*:*:*:*:*:*:*"},
+        {"referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"}
+      ]
+    },
+    {"SPDXID": "SPDXRef-zlib", "name": "zlib", "versionInfo": "1.2.13"}
+  ],
+  "files": [
+    {"SPDXID": "SPDXRef-file-1", "fileName": "/opt/app/lib/log4j-core-2.14.1.jar"}
+  ],
+  "relationships": [
+    {"spdxElementId": "SPDXRef-image", "relationshipType": "CONTAINS", "relatedSpdxElement": "SPDXRef-log4j"},
+    {"spdxElementId": "SPDXRef-log4j", "relationshipType": "CONTAINS", "relatedSpdxElement": "SPDXRef-file-1"}
+  ]
+}"#;
+
+    const SPDX_TAG_VALUE_STR: &str = "SPDXVersion: SPDX-2.3
+DataLicense: CC0-1.0
+SPDXID: SPDXRef-DOCUMENT
+DocumentName: app-sbom
+DocumentComment: <text>generated by a scanner:
+not a tag</text>
+
+PackageName: log4j-core
+SPDXID: SPDXRef-log4j
+PackageVersion: 2.14.1
+ExternalRef: PACKAGE-MANAGER purl pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1
+ExternalRef: SECURITY cpe23Type cpe:2.3:a:apache:log4j:2.14.1:*:*:*:*:*:*:*
+
+FileName: /opt/app/lib/log4j-core-2.14.1.jar
+SPDXID: SPDXRef-file-1
+
+Relationship: SPDXRef-file-1 GENERATED_FROM SPDXRef-log4j
+";
+
+    #[test]
+    fn test_parser_json() {
+        let findings = parser_json(SPDX_JSON_STR).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].object, "registry.example.com/app:1.0");
+        assert_eq!(findings[0].component_id(), "log4j-core2.14.1");
+        assert_eq!(findings[0].path, "/opt/app/lib/log4j-core-2.14.1.jar");
+        assert_eq!(findings[0].purl, "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1");
+        assert_eq!(findings[0].cpe, "cpe:2.3:a:apache:log4j:2.14.1:*:*:*:*:*:*:*");
+        assert!(findings[0].cve.is_empty());
+        assert_eq!(findings[1].path, "");
+        assert!(parser_json(r#"{"bomFormat": "CycloneDX"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parser_tag_value() {
+        let findings = parser_tag_value(SPDX_TAG_VALUE_STR).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].object, "app-sbom");
+        assert_eq!(findings[0].version, "2.14.1");
+        assert_eq!(findings[0].path, "/opt/app/lib/log4j-core-2.14.1.jar");
+        assert_eq!(findings[0].purl, "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1");
+        assert_eq!(findings[0].cpe, "cpe:2.3:a:apache:log4j:2.14.1:*:*:*:*:*:*:*");
+        assert!(parser_tag_value("DocumentName: x").is_err());
+    }
+}
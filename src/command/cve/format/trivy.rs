@@ -0,0 +1,169 @@
+use crate::command::cve::format::Finding;
+use crate::command::lib::text;
+use encoding_rs::Encoding;
+use serde::{self, Deserialize};
+use std::error::Error;
+
+/// A `trivy image --format json` report (schema version 2).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyReport {
+    #[serde(default)]
+    artifact_name: String,
+    #[serde(default)]
+    results: Vec<TrivyResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyResult {
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    vulnerabilities: Option<Vec<TrivyVulnerability>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrivyVulnerability {
+    #[serde(rename = "VulnerabilityID")]
+    vulnerability_id: String,
+    #[serde(rename = "PkgName", default)]
+    pkg_name: String,
+    #[serde(rename = "InstalledVersion", default)]
+    installed_version: String,
+    /// Set for language packages, e.g. the jar a Java library was found in.
+    #[serde(rename = "PkgPath", default)]
+    pkg_path: String,
+    #[serde(rename = "PkgIdentifier", default)]
+    pkg_identifier: TrivyPkgIdentifier,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TrivyPkgIdentifier {
+    #[serde(rename = "PURL", default)]
+    purl: String,
+}
+
+pub fn load(path: &str, encoding: Option<&'static Encoding>) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let data = text::read(path, encoding)?;
+    parser_json(&data)
+}
+
+/// One finding per vulnerability of every result, OS packages are reported in the OS of the
+/// result target.
+pub fn parser_json(data: &str) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let report: TrivyReport = serde_json::from_str(data)?;
+    let mut findings = Vec::new();
+    for result in report.results {
+        let target = target_path(&report.artifact_name, &result.target);
+        for vulnerability in result.vulnerabilities.unwrap_or_default() {
+            let path = if vulnerability.pkg_path.is_empty() {
+                target.clone()
+            } else {
+                vulnerability.pkg_path
+            };
+            findings.push(Finding {
+                object: report.artifact_name.clone(),
+                component: vulnerability.pkg_name,
+                version: vulnerability.installed_version,
+                path,
+                cve: vulnerability.vulnerability_id,
+                purl: vulnerability.pkg_identifier.purl,
+                ..Finding::default()
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// The target without the artifact name, which is the finding object already, so
+/// `registry.example.com/app:1.0 (alpine 3.10.2)` is `alpine 3.10.2`.
+fn target_path(artifact_name: &str, target: &str) -> String {
+    match target.strip_prefix(artifact_name) {
+        Some(os) if !artifact_name.is_empty() => os
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .to_string(),
+        _ => target.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const TRIVY_JSON_STR: &str = r#"{
+  "SchemaVersion": 2,
+  "ArtifactName": "registry.example.com/app:1.0",
+  "ArtifactType": "container_image",
+  "Results": [
+    {
+      "Target": "registry.example.com/app:1.0 (alpine 3.10.2)",
+      "Class": "os-pkgs",
+      "Type": "alpine",
+      "Vulnerabilities": [
+        {
+          "VulnerabilityID": "CVE-2019-1549",
+          "PkgID": "libcrypto1.1@1.1.1c-r0",
+          "PkgName": "libcrypto1.1",
+          "InstalledVersion": "1.1.1c-r0",
+          "FixedVersion": "1.1.1d-r0",
+          "Severity": "MEDIUM"
+        }
+      ]
+    },
+    {
+      "Target": "Java",
+      "Class": "lang-pkgs",
+      "Type": "jar",
+      "Vulnerabilities": [
+        {
+          "VulnerabilityID": "CVE-2021-44228",
+          "PkgName": "org.apache.logging.log4j:log4j-core",
+          "PkgIdentifier": {
+            "PURL": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+          },
+          "PkgPath": "opt/app/lib/log4j-core-2.14.1.jar",
+          "InstalledVersion": "2.14.1",
+          "FixedVersion": "2.15.0",
+          "Severity": "CRITICAL"
+        },
+        {
+          "VulnerabilityID": "GHSA-jfh8-c2jp-5v3q",
+          "PkgName": "org.apache.logging.log4j:log4j-core",
+          "PkgPath": "opt/app/lib/log4j-core-2.14.1.jar",
+          "InstalledVersion": "2.14.1",
+          "Severity": "CRITICAL"
+        }
+      ]
+    },
+    {
+      "Target": "opt/app/package-lock.json",
+      "Class": "lang-pkgs",
+      "Type": "npm",
+      "Vulnerabilities": null
+    }
+  ]
+}"#;
+
+    #[test]
+    fn test_parser_json() {
+        let findings = parser_json(TRIVY_JSON_STR).unwrap();
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].object, "registry.example.com/app:1.0");
+        assert_eq!(findings[0].component_id(), "libcrypto1.11.1.1c-r0");
+        assert_eq!(findings[0].path, "alpine 3.10.2");
+        assert_eq!(findings[0].object_path(), "registry.example.com/app:1.0/alpine 3.10.2");
+        assert_eq!(findings[1].cve, "CVE-2021-44228");
+        assert_eq!(
+            findings[1].object_path(),
+            "registry.example.com/app:1.0/opt/app/lib/log4j-core-2.14.1.jar"
+        );
+        assert_eq!(findings[1].purl, "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1");
+        assert!(parser_json("{}").unwrap().is_empty());
+        assert_eq!(target_path("app.tar", "app.tar"), "");
+        assert_eq!(target_path("", "Cargo.lock"), "Cargo.lock");
+    }
+}
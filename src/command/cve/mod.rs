@@ -1,15 +1,28 @@
 use std::{sync::Mutex, vec};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-use clap::{value_parser, App, Arg, ArgAction, Command};
+use clap::{value_parser, App, Arg, ArgAction, ArgMatches, Command};
 
 pub mod api;
 pub mod utils;
 pub mod exporter;
 pub mod analyze;
+pub mod columns;
+pub mod format;
+pub mod search;
 
 use api::lib::CveApis;
-use crate::command::cve::api::aliyun_api::AsyncAliyunApi;
+use crate::command::cve::api::aliyun_api::{AliyunSelectors, AsyncAliyunApi};
+use crate::command::cve::api::base::{HttpConfig, HttpOptions};
+use crate::command::cve::api::cache::CveCache;
+use crate::command::cve::api::cvelist_api::CveListApi;
+use crate::command::cve::api::epss::Epss;
+use crate::command::cve::api::ghsa_api::GhsaApi;
+use crate::command::cve::api::kev::Kev;
+use crate::command::cve::api::nvd_api::NvdApi;
+use crate::command::cve::api::osv_api::OsvApi;
+use crate::command::lib::{table, text};
 
 // use crate::command::lib::image;
 //
@@ -23,10 +36,9 @@ lazy_static! {
         cve_apis.register(Box::new(api::aliyun_api::AliyunApi::new()));
         Arc::new(Mutex::new(cve_apis))
     };
-    pub static ref ALIYUN_CVE_API: Arc<Mutex<AsyncAliyunApi>> = {
-        let aliyun_api = AsyncAliyunApi::new();
-        Arc::new(Mutex::new(aliyun_api))
-    };
+    pub static ref ALIYUN_CVE_API: Arc<AsyncAliyunApi> = Arc::new(AsyncAliyunApi::new());
+    pub static ref EPSS: RwLock<Epss> = RwLock::new(Epss::new());
+    pub static ref KEV: RwLock<Kev> = RwLock::new(Kev::new());
 }
 
 pub fn new_sub_command<'help>() -> App<'help> {
@@ -46,19 +58,19 @@ pub fn new_sub_command<'help>() -> App<'help> {
                         .value_parser(value_parser!(String))
                         .default_values(&["./Open_Source_Binary_Result.xlsx"])
                         .short('f')
-                        .help("待处理的Excel文件路径"),
+                        .help("待处理的报告文件路径"),
                 )
                 .arg(
                     Arg::new("sheet")
                         .default_value("组件报告")
                         .long("sheet")
-                        .help("待处理的Excel文件表格名称"),
+                        .help("待处理的Excel文件组件表格名称, 不存在或表头不匹配时按表头自动识别"),
                 )
                 .arg(
                     Arg::new("sheet_ext")
                         .default_value("漏洞报告")
                         .long("sheet_ext")
-                        .help("待处理的Excel文件表格名称"),
+                        .help("待处理的Excel文件漏洞表格名称, 不存在或表头不匹配时按表头自动识别"),
                 )
                 .arg(
                     Arg::new("output")
@@ -78,6 +90,34 @@ pub fn new_sub_command<'help>() -> App<'help> {
                         .action(clap::ArgAction::SetTrue)
                         .help("是否解析release包"),
                 )
+                .arg(
+                    Arg::new("format")
+                        .default_value(format::FORMAT_BDBA)
+                        .value_parser([
+                            format::FORMAT_BDBA,
+                            format::FORMAT_TRIVY,
+                            format::FORMAT_GRYPE,
+                            format::FORMAT_CYCLONEDX,
+                            format::FORMAT_SPDX,
+                        ])
+                        .long("format")
+                        .help("待处理文件的格式(bdba: BDBA Excel/CSV报告(.csv文件, CSV目录或.zip包)或REST API JSON结果, trivy: Trivy JSON报告, grype: Grype JSON报告, cyclonedx: CycloneDX JSON/XML SBOM, spdx: SPDX 2.3 JSON/tag-value SBOM; SBOM中未列出漏洞的组件按purl由--osv/--ghsa本地数据匹配, 支持语言包及deb/apk/rpm系统包)"),
+                )
+                .arg(
+                    Arg::new("vex_annotate")
+                        .long("vex-annotate")
+                        .action(ArgAction::SetTrue)
+                        .help("保留VEX标记为not_affected/false_positive的漏洞并在image表中标注, 默认排除"),
+                )
+                .arg(
+                    Arg::new("columns")
+                        .takes_value(true)
+                        .long("columns")
+                        .help("表头映射配置文件(TOML/YAML, 按别名/正则/固定列识别字段), 默认使用BDBA表头"),
+                )
+                .arg(table::delimiter_arg())
+                .arg(text::encoding_arg())
+                .args(provider_args())
                 .override_usage("etool cve analyze -p ./tmp -f Open_Source_Binary_Result.xlsx --sheet 组件报告 --sheet_ext 漏洞报告 --detail --release -o cve.xlsx\n  "),
             Command::new("export")
                 .about("导出CVE漏洞库信息").arg(
@@ -106,7 +146,296 @@ pub fn new_sub_command<'help>() -> App<'help> {
                         .action(clap::ArgAction::SetTrue)
                         .help("是否输出CVE详细信息"),
                 )
-                .override_usage("etool cve export -p ./tmp -f cve.json --detail -o cve-export.xlsx\n  ")
+                .arg(
+                    Arg::new("jobs")
+                        .default_value("8")
+                        .value_parser(value_parser!(usize))
+                        .long("jobs")
+                        .help("并发获取CVE详细信息的任务数"),
+                )
+                .arg(text::encoding_arg())
+                .args(provider_args())
+                .override_usage("etool cve export -p ./tmp -f cve.json --detail -o cve-export.xlsx\n  "),
+            Command::new("search")
+                .about("按组件搜索阿里云漏洞库, 获取CVE编号").arg(
+                Arg::new("path")
+                    .default_value("./tmp")
+                    .short('p')
+                    .help("生成的目标目录"),
+            )
+                .arg(
+                    Arg::new("name")
+                        .required(true)
+                        .takes_value(true)
+                        .short('n')
+                        .long("name")
+                        .help("组件名称(如 kafka)"),
+                )
+                .arg(
+                    Arg::new("version")
+                        .default_value("")
+                        .long("version")
+                        .help("组件版本或版本范围(如 3.0.0 或 >=2.0,<3.0), 默认不限版本"),
+                )
+                .arg(
+                    Arg::new("pages")
+                        .default_value("5")
+                        .value_parser(value_parser!(usize))
+                        .long("pages")
+                        .help("最多读取的搜索结果页数"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .default_value("8")
+                        .value_parser(value_parser!(usize))
+                        .long("jobs")
+                        .help("并发获取CVE详细信息的任务数"),
+                )
+                .arg(
+                    Arg::new("url")
+                        .default_value(api::aliyun_search::AVD_URL)
+                        .long("url")
+                        .help("阿里云漏洞库地址"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .default_value("cve.json")
+                        .short('o')
+                        .help("输出的CVE编号列表文件(可作为cve export的输入)"),
+                )
+                .args(http_args())
+                .override_usage("etool cve search -p ./tmp -n kafka --version 3.0.0 -o cve.json\n  ")
         ]).override_usage("")
 }
 
+fn provider_args<'help>() -> Vec<Arg<'help>> {
+    vec![
+        Arg::new("provider")
+            .action(ArgAction::Append)
+            .value_delimiter(',')
+            .default_values(&[api::aliyun_api::ALI_YUN_CVE_API])
+            .value_parser([
+                api::aliyun_api::ALI_YUN_CVE_API,
+                api::nvd_api::NVD_CVE_API,
+                api::osv_api::OSV_CVE_API,
+                api::cvelist_api::CVE_LIST_API,
+                api::ghsa_api::GHSA_CVE_API,
+            ])
+            .long("provider")
+            .help("CVE详细信息的数据源, 按顺序逐字段补全(如 NvdApi,OsvApi,AliyunApi)"),
+        Arg::new("nvd")
+            .takes_value(true)
+            .long("nvd")
+            .help("本地NVD JSON 2.0数据文件或目录(nvdcve-2.0-*.json[.gz])"),
+        Arg::new("osv")
+            .takes_value(true)
+            .long("osv")
+            .help("本地OSV数据导出文件或目录(<ecosystem>/all.zip)"),
+        Arg::new("cvelist")
+            .takes_value(true)
+            .long("cvelist")
+            .help("本地cvelistV5仓库目录(cves/YYYY/NNxxx/CVE-*.json)"),
+        Arg::new("cache")
+            .takes_value(true)
+            .long("cache")
+            .help("CVE详细信息缓存目录(默认为<path>/cve-cache)"),
+        Arg::new("cache_ttl")
+            .default_value("168")
+            .value_parser(value_parser!(u64))
+            .long("cache-ttl")
+            .help("CVE详细信息缓存有效期(小时)"),
+        Arg::new("refresh")
+            .long("refresh")
+            .action(ArgAction::SetTrue)
+            .help("忽略缓存, 重新获取CVE详细信息"),
+        Arg::new("ghsa")
+            .takes_value(true)
+            .long("ghsa")
+            .help("本地GitHub advisory-database仓库目录(advisories/github-reviewed)"),
+        Arg::new("epss")
+            .takes_value(true)
+            .long("epss")
+            .help("本地FIRST EPSS每日数据文件(epss_scores-YYYY-MM-DD.csv[.gz]), 输出漏洞利用概率和百分位"),
+        Arg::new("kev")
+            .takes_value(true)
+            .long("kev")
+            .help("本地CISA KEV目录文件(known_exploited_vulnerabilities.json), 标记已知被利用漏洞"),
+    ]
+    .into_iter()
+    .chain(http_args())
+    .collect()
+}
+
+/// The network options shared by every subcommand reaching a remote provider.
+fn http_args<'help>() -> Vec<Arg<'help>> {
+    vec![
+        Arg::new("offline")
+            .long("offline")
+            .action(ArgAction::SetTrue)
+            .help("离线模式, 禁止访问网络, 仅使用本地数据源和缓存"),
+        Arg::new("retries")
+            .takes_value(true)
+            .value_parser(value_parser!(u32))
+            .long("retries")
+            .help("网络请求失败或被限流时的重试次数(默认3)"),
+        Arg::new("rps")
+            .takes_value(true)
+            .value_parser(value_parser!(f64))
+            .long("rps")
+            .help("每个站点每秒最多请求数(默认0, 不限制)"),
+        Arg::new("config")
+            .takes_value(true)
+            .long("config")
+            .help("配置文件路径(TOML, [http]表配置代理/TLS/超时, [aliyun]表配置阿里云漏洞页面解析规则)"),
+        Arg::new("proxy")
+            .takes_value(true)
+            .long("proxy")
+            .help("HTTP/HTTPS代理地址, 默认读取HTTP_PROXY/HTTPS_PROXY环境变量, 未配置则不使用代理"),
+        Arg::new("proxy_auth")
+            .takes_value(true)
+            .long("proxy-auth")
+            .help("代理认证信息(user:password)"),
+        Arg::new("no_proxy")
+            .takes_value(true)
+            .long("no-proxy")
+            .help("不使用代理的地址列表, 逗号分隔, 默认读取NO_PROXY环境变量"),
+        Arg::new("ca_cert")
+            .takes_value(true)
+            .long("ca-cert")
+            .help("自定义CA证书文件(PEM)"),
+        Arg::new("insecure")
+            .long("insecure")
+            .action(ArgAction::SetTrue)
+            .help("不校验服务端TLS证书"),
+        Arg::new("connect_timeout")
+            .takes_value(true)
+            .value_parser(value_parser!(u64))
+            .long("connect-timeout")
+            .help("连接超时时间(秒, 默认2)"),
+        Arg::new("timeout")
+            .takes_value(true)
+            .value_parser(value_parser!(u64))
+            .long("timeout")
+            .help("请求超时时间(秒, 默认10)"),
+    ]
+}
+
+/// Http options from the environment, then the config file, then the command line.
+fn http_options(matches: &ArgMatches) -> HttpOptions {
+    let mut options = HttpOptions::default();
+    options.apply_env();
+    if let Some(path) = matches.get_one::<String>("config") {
+        match HttpConfig::load(path) {
+            Ok(config) => options.apply_config(config),
+            Err(e) => panic!("read config {} error: {}", path, e),
+        }
+    }
+
+    options.offline = matches.get_flag("offline");
+    if let Some(v) = matches.get_one::<String>("proxy") {
+        options.http_proxy = Some(v.clone());
+        options.https_proxy = Some(v.clone());
+    }
+    if let Some(v) = matches.get_one::<String>("proxy_auth") {
+        options.proxy_auth = Some(v.clone());
+    }
+    if let Some(v) = matches.get_one::<String>("no_proxy") {
+        options.no_proxy = Some(v.clone());
+    }
+    if let Some(v) = matches.get_one::<String>("ca_cert") {
+        options.ca_cert = Some(v.clone());
+    }
+    if matches.get_flag("insecure") {
+        options.insecure = true;
+    }
+    if let Some(v) = matches.get_one::<u64>("connect_timeout") {
+        options.connect_timeout = Duration::from_secs(*v);
+    }
+    if let Some(v) = matches.get_one::<u64>("timeout") {
+        options.timeout = Duration::from_secs(*v);
+    }
+    if let Some(v) = matches.get_one::<u32>("retries") {
+        options.retries = *v;
+    }
+    if let Some(v) = matches.get_one::<f64>("rps") {
+        options.rps = *v;
+    }
+
+    // fail here instead of inside the lazily built clients
+    if let Err(e) = options.proxies() {
+        panic!("invalid proxy: {}", e)
+    }
+    if let Err(e) = options.ca_certificate() {
+        panic!("invalid ca cert: {}", e)
+    }
+    options
+}
+
+/// Applies the http options and the Aliyun page selectors of `http_args`.
+pub fn configure_http(matches: &ArgMatches) {
+    api::base::configure(http_options(matches));
+    if let Some(path) = matches.get_one::<String>("config") {
+        match AliyunSelectors::load(path) {
+            Ok(selectors) => api::aliyun_api::configure(selectors),
+            Err(e) => panic!("read config {} error: {}", path, e),
+        }
+    }
+}
+
+/// Registers the local providers given on the command line and sets the provider chain.
+pub fn register_providers(matches: &ArgMatches) {
+    // the http clients read their options when CVE_API and ALIYUN_CVE_API are first used
+    let offline = matches.get_flag("offline");
+    configure_http(matches);
+
+    let mut cve_apis = CVE_API.lock().unwrap();
+    cve_apis.set_offline(offline);
+    let cache_dir = match matches.get_one::<String>("cache") {
+        Some(v) => v.clone(),
+        None => format!("{}/cve-cache", matches.get_one::<String>("path").unwrap()),
+    };
+    let cache_ttl = matches.get_one::<u64>("cache_ttl").unwrap();
+    cve_apis.set_cache(CveCache::new(&cache_dir, cache_ttl * 3600, matches.get_flag("refresh")));
+    if let Some(path) = matches.get_one::<String>("nvd") {
+        cve_apis.register(Box::new(NvdApi::load(path)));
+    }
+    if let Some(path) = matches.get_one::<String>("osv") {
+        cve_apis.register(Box::new(OsvApi::load(path)));
+    }
+    if let Some(path) = matches.get_one::<String>("cvelist") {
+        cve_apis.register(Box::new(CveListApi::new(path)));
+    }
+    if let Some(path) = matches.get_one::<String>("ghsa") {
+        cve_apis.register(Box::new(GhsaApi::load(path)));
+    }
+    if let Some(path) = matches.get_one::<String>("epss") {
+        match Epss::load(path) {
+            Ok(epss) => {
+                println!("{}", epss.stats());
+                *EPSS.write().unwrap() = epss;
+            }
+            Err(e) => panic!("read epss {} error: {}", path, e),
+        }
+    }
+    if let Some(path) = matches.get_one::<String>("kev") {
+        match Kev::load(path) {
+            Ok(kev) => {
+                println!("{}", kev.stats());
+                *KEV.write().unwrap() = kev;
+            }
+            Err(e) => panic!("read kev {} error: {}", path, e),
+        }
+    }
+
+    let chain: Vec<String> = matches
+        .get_many::<String>("provider")
+        .unwrap()
+        .map(|x| x.to_string())
+        .collect();
+    for provider in chain.iter() {
+        if !cve_apis.contains(provider) {
+            panic!("cve provider not registered {}", provider)
+        }
+    }
+    cve_apis.set_chain(chain);
+}
@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use clap::ArgMatches;
+use crate::command::cve::api::aliyun_search::AvdSearch;
+use crate::command::cve::api::version::VersionRange;
+use tokio;
+
+
+pub fn handler(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("path").unwrap();
+    let name = matches.get_one::<String>("name").unwrap();
+    let version = matches.get_one::<String>("version").unwrap();
+    let pages = *matches.get_one::<usize>("pages").unwrap();
+    let jobs = *matches.get_one::<usize>("jobs").unwrap();
+    let url = matches.get_one::<String>("url").unwrap();
+    let output = matches.get_one::<String>("output").unwrap();
+    super::configure_http(matches);
+
+    let range = match VersionRange::parse(version) {
+        Ok(v) => v,
+        Err(e) => panic!("{}", e),
+    };
+
+    if !Path::exists(Path::new(path)) {
+        fs::create_dir(path).unwrap();
+    };
+
+    let search = Arc::new(AvdSearch::new(url));
+    let records = match tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(search.search(name, &range, pages, jobs))
+    {
+        Ok(v) => v,
+        Err(e) => panic!("search {} error: {}", name, e),
+    };
+
+    let mut ids: Vec<String> = Vec::new();
+    for record in records.iter() {
+        println!("{}: {}", record.id, record.title);
+        if !ids.contains(&record.id) {
+            ids.push(record.id.clone());
+        }
+    }
+
+    let output = format!("{}/{}", path, output);
+    fs::write(&output, serde_json::to_string_pretty(&ids).unwrap()).unwrap();
+    println!("cve num: {:?}\noutput: {:#?}", ids.len(), output);
+}
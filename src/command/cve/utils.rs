@@ -1,49 +1,15 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use xlsxwriter::{Format, Workbook, Worksheet};
 use xlsxwriter::format::{FormatAlignment, FormatColor, FormatVerticalAlignment};
-use crate::command::cve::{api, CVE_API, ALIYUN_CVE_API, TITLE_FONT_SIZE};
+use crate::command::cve::{api, CVE_API, ALIYUN_CVE_API, EPSS, KEV, TITLE_FONT_SIZE};
+use crate::command::cve::api::lib::{CveRecord, MergedCve};
+use crate::command::cve::api::record::Severity;
 
-pub fn is_column_field_component(s: &str) -> bool {
-    if s == "Component" || s == "组件名称" {
-        return true;
-    }
-    false
-}
-
-pub fn is_column_field_version(s: &str) -> bool {
-    if s == "Version" || s == "组件版本" {
-        return true;
-    }
-    false
-}
-
-pub fn is_column_field_object(s: &str) -> bool {
-    if s == "Object full path" || s == "文件路径" {
-        return true;
-    }
-    false
-}
-
-pub fn is_column_field_vul_count(s: &str) -> bool {
-    if s == "Vulnerability count" || s == "漏洞数量" {
-        return true;
-    }
-    false
-}
-
-pub fn is_column_field_cve(s: &str) -> bool {
-    if s == "CVE" || s == "CVE编号" {
-        return true;
-    }
-    false
-}
-
-pub fn is_column_field_binary_object(s: &str) -> bool {
-    if s =="Object" || s == "文件名" {
-        return true;
-    }
-    false
-}
+/// Written to the source column of CVEs no provider could answer.
+const UNRESOLVED: &str = "unresolved";
 
 pub fn set_title_format() -> Format {
     let mut format = Format::new();
@@ -60,10 +26,37 @@ pub fn set_content_format() -> Format {
     format
 }
 
+/// The content format with a background by severity, for sorting and filtering by color.
+pub fn set_severity_format(severity: Severity) -> Format {
+    let mut format = set_content_format();
+    let color = match severity {
+        Severity::Critical => FormatColor::Red,
+        Severity::High => FormatColor::Orange,
+        Severity::Medium => FormatColor::Custom(0xFFEB9C),
+        Severity::Low => FormatColor::Custom(0xC6EFCE),
+        Severity::None => FormatColor::Silver,
+    };
+    format.set_bg_color(color);
+    format
+}
+
+/// The content format of CISA KEV entries, bold red so they stand out next to the severity colors.
+pub fn set_kev_format() -> Format {
+    let mut format = set_content_format();
+    format.set_bold().set_font_color(FormatColor::Red);
+    format
+}
+
+/// True when `id` is in the loaded CISA KEV catalog.
+pub fn is_kev(id: &str) -> bool {
+    KEV.read().unwrap().contains(id)
+}
+
 pub fn write_cve_output(cve_map: &HashMap<String, String>, out: &mut Workbook, detail: bool) {
     if !cve_map.is_empty() {
         let format1 = set_title_format();
         let format2 = set_content_format();
+        let format3 = set_kev_format();
 
 
         let mut cve_keys: Vec<String> = cve_map.keys().map(|x| x.to_string()).collect();
@@ -78,64 +71,49 @@ pub fn write_cve_output(cve_map: &HashMap<String, String>, out: &mut Workbook, d
             }
             index = index % 60000;
             if let Some(v) = cve_map.get(k) {
+                let cve_format = if is_kev(k) { &format3 } else { &format2 };
                 sheet1
-                    .write_string((index + 1) as u32, 0, k, Some(&format2))
+                    .write_string((index + 1) as u32, 0, k, Some(cve_format))
                     .unwrap();
                 println!("{}:{}", k, v);
                 sheet1
                     .write_url((index + 1) as u32, 1, v, Some(&format2))
                     .unwrap();
+                write_cve_epss(&mut sheet1, (index + 1) as u32, k, &format2);
+                write_cve_kev(&mut sheet1, (index + 1) as u32, k, &format3);
                 if !detail {
                     // need parser CVE detail info
                     continue;
                 }
-                let ret = CVE_API.lock().unwrap().invoke(api::aliyun_api::ALI_YUN_CVE_API, k);
+                let ret = CVE_API.lock().unwrap().invoke_chain(k);
+                if ret.is_empty() {
+                    sheet1
+                        .write_string((index + 1) as u32, 9, UNRESOLVED, Some(&format2))
+                        .unwrap();
+                    continue;
+                }
                 println!("{:#?}", ret.to_json());
-                sheet1
-                    .write_string((index + 1) as u32, 2, &ret.get("title"), Some(&format2))
-                    .unwrap();
-                sheet1
-                    .write_string((index + 1) as u32, 3, &ret.get("fix_label"), Some(&format2))
-                    .unwrap();
-                sheet1
-                    .write_string((index + 1) as u32, 4, &ret.get("publish"), Some(&format2))
-                    .unwrap();
-                sheet1
-                    .write_string(
-                        (index + 1) as u32,
-                        5,
-                        &ret.get("description"),
-                        Some(&format2),
-                    )
-                    .unwrap();
-                sheet1
-                    .write_string(
-                        (index + 1) as u32,
-                        6,
-                        &ret.get("suggestion"),
-                        Some(&format2),
-                    )
-                    .unwrap();
-                sheet1
-                    .write_string((index + 1) as u32, 7, &ret.get("score"), Some(&format2))
-                    .unwrap();
-                sheet1
-                    .write_string((index + 1) as u32, 8, &ret.get("effect"), Some(&format2))
-                    .unwrap();
+                write_cve_detail(&mut sheet1, (index + 1) as u32, &ret, &format2);
             }
         }
     }
 }
 
-pub async fn write_cve_output_async(cve_map: &HashMap<String, String>, out: &mut Workbook, detail: bool) {
+pub async fn write_cve_output_async(cve_map: &HashMap<String, String>, out: &mut Workbook, detail: bool, jobs: usize) {
     if !cve_map.is_empty() {
         let format1 = set_title_format();
         let format2 = set_content_format();
+        let format3 = set_kev_format();
 
 
         let mut cve_keys: Vec<String> = cve_map.keys().map(|x| x.to_string()).collect();
         cve_keys.sort();
 
+        let mut details: HashMap<String, Result<MergedCve, String>> = HashMap::new();
+        if detail {
+            details = query_chain_all(&cve_keys, jobs).await;
+        }
+
         let sheet_name = format!("cve-{}", 0);
         let mut sheet1: Worksheet = add_worksheet(out, sheet_name, &format1);
         for (mut index, k) in cve_keys.iter().enumerate() {
@@ -145,54 +123,34 @@ pub async fn write_cve_output_async(cve_map: &HashMap<String, String>, out: &mut
             }
             index = index % 60000;
             if let Some(v) = cve_map.get(k) {
+                let cve_format = if is_kev(k) { &format3 } else { &format2 };
                 sheet1
-                    .write_string((index + 1) as u32, 0, k, Some(&format2))
+                    .write_string((index + 1) as u32, 0, k, Some(cve_format))
                     .unwrap();
                 println!("{}:{}", k, v);
                 sheet1
                     .write_url((index + 1) as u32, 1, v, Some(&format2))
                     .unwrap();
+                write_cve_epss(&mut sheet1, (index + 1) as u32, k, &format2);
+                write_cve_kev(&mut sheet1, (index + 1) as u32, k, &format3);
                 if !detail {
                     // need parser CVE detail info
                     continue;
                 }
-                let ret = ALIYUN_CVE_API.lock().unwrap().query(k).await;
-                match ret {
-                    Ok(ret) => {
-                        sheet1
-                            .write_string((index + 1) as u32, 2, &ret.get("title"), Some(&format2))
-                            .unwrap();
-                        sheet1
-                            .write_string((index + 1) as u32, 3, &ret.get("fix_label"), Some(&format2))
-                            .unwrap();
-                        sheet1
-                            .write_string((index + 1) as u32, 4, &ret.get("publish"), Some(&format2))
-                            .unwrap();
-                        sheet1
-                            .write_string(
-                                (index + 1) as u32,
-                                5,
-                                &ret.get("description"),
-                                Some(&format2),
-                            )
-                            .unwrap();
-                        sheet1
-                            .write_string(
-                                (index + 1) as u32,
-                                6,
-                                &ret.get("suggestion"),
-                                Some(&format2),
-                            )
-                            .unwrap();
+                match details.get(k) {
+                    Some(Ok(ret)) if !ret.is_empty() => {
+                        write_cve_detail(&mut sheet1, (index + 1) as u32, ret, &format2);
+                    },
+                    Some(Err(e)) => {
+                        println!("{}", e);
                         sheet1
-                            .write_string((index + 1) as u32, 7, &ret.get("score"), Some(&format2))
+                            .write_string((index + 1) as u32, 9, UNRESOLVED, Some(&format2))
                             .unwrap();
+                    }
+                    _ => {
                         sheet1
-                            .write_string((index + 1) as u32, 8, &ret.get("effect"), Some(&format2))
+                            .write_string((index + 1) as u32, 9, UNRESOLVED, Some(&format2))
                             .unwrap();
-                    },
-                    Err(e) => {
-                        println!("{}", e)
                     }
                 }
             }
@@ -200,6 +158,132 @@ pub async fn write_cve_output_async(cve_map: &HashMap<String, String>, out: &mut
     }
 }
 
+/// Writes the detail columns of one row of the cve sheet.
+fn write_cve_detail(sheet: &mut Worksheet, row: u32, ret: &MergedCve, format: &Format) {
+    let record = ret.record();
+    let published = record.published.map(|x| x.to_string()).unwrap_or_default();
+    sheet.write_string(row, 2, &record.title, Some(format)).unwrap();
+    sheet.write_string(row, 3, &record.fix_hint(), Some(format)).unwrap();
+    sheet.write_string(row, 4, &published, Some(format)).unwrap();
+    sheet.write_string(row, 5, &record.description, Some(format)).unwrap();
+    sheet.write_string(row, 6, &record.suggestion, Some(format)).unwrap();
+    if let Some(score) = record.score() {
+        sheet.write_number(row, 7, score, Some(format)).unwrap();
+    }
+    if let Some(severity) = record.severity() {
+        let severity_format = set_severity_format(severity);
+        sheet.write_string(row, 8, severity.as_str(), Some(&severity_format)).unwrap();
+    }
+    sheet.write_string(row, 9, &ret.sources(), Some(format)).unwrap();
+    sheet.write_string(row, 10, record.vector().unwrap_or_default(), Some(format)).unwrap();
+}
+
+/// Writes the EPSS probability and percentile of `id`, if the loaded snapshot has it.
+fn write_cve_epss(sheet: &mut Worksheet, row: u32, id: &str, format: &Format) {
+    if let Some(score) = EPSS.read().unwrap().get(id) {
+        sheet.write_number(row, 11, score.epss, Some(format)).unwrap();
+        sheet.write_number(row, 12, score.percentile, Some(format)).unwrap();
+    }
+}
+
+/// Writes the date added, due date and required action of `id`, if it is a CISA KEV entry.
+fn write_cve_kev(sheet: &mut Worksheet, row: u32, id: &str, format: &Format) {
+    if let Some(entry) = KEV.read().unwrap().get(id) {
+        sheet.write_string(row, 13, &entry.date_added, Some(format)).unwrap();
+        sheet.write_string(row, 14, &entry.due_date, Some(format)).unwrap();
+        sheet.write_string(row, 15, &entry.required_action, Some(format)).unwrap();
+    }
+}
+
+/// Runs `query_chain_async` for every id with at most `jobs` queries in flight.
+async fn query_chain_all(ids: &[String], jobs: usize) -> HashMap<String, Result<MergedCve, String>> {
+    let semaphore = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks = JoinSet::new();
+    for id in ids.iter().cloned() {
+        let permit = semaphore.clone().acquire_owned().await.unwrap();
+        tasks.spawn(async move {
+            let ret = query_chain_async(&id).await;
+            drop(permit);
+            (id, ret)
+        });
+    }
+
+    let mut result = HashMap::new();
+    while let Some(ret) = tasks.join_next().await {
+        match ret {
+            Ok((id, ret)) => {
+                println!("{}: {}/{}", id, result.len() + 1, ids.len());
+                result.insert(id, ret);
+            }
+            Err(e) => {
+                println!("{:#?}", e);
+            }
+        }
+    }
+    result
+}
+
+/// Same as `CveApis::invoke_chain`, but asks Aliyun through the async client.
+async fn query_chain_async(id: &str) -> Result<MergedCve, String> {
+    let chain = CVE_API.lock().unwrap().chain();
+    let mut merged = MergedCve::new();
+    let mut last_error = None;
+    for key in chain.iter() {
+        if merged.is_complete() {
+            break;
+        }
+        if key == api::aliyun_api::ALI_YUN_CVE_API {
+            let (cached, offline) = {
+                let cve_apis = CVE_API.lock().unwrap();
+                (cve_apis.cache_get(key, id), cve_apis.is_offline())
+            };
+            if let Some(cve) = cached {
+                merged.merge(key, &cve);
+                continue;
+            }
+            if offline {
+                continue;
+            }
+            match ALIYUN_CVE_API.query(id).await {
+                Ok(cve) => {
+                    CVE_API.lock().unwrap().cache_put(key, id, &cve);
+                    merged.merge(key, &cve)
+                }
+                Err(e) => last_error = Some(e.to_string()),
+            }
+        } else {
+            // local providers read files, keep them off the runtime workers
+            let (provider_key, provider_id) = (key.clone(), id.to_string());
+            let cve = tokio::task::spawn_blocking(move || invoke_unlocked(&provider_key, &provider_id)).await;
+            if let Ok(Some(cve)) = cve {
+                merged.merge(key, &cve);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if merged.is_empty() => Err(e),
+        _ => Ok(merged),
+    }
+}
+
+/// Same as `CveApis::invoke`, but the registry is only locked around the cache and not while
+/// the provider answers, so parallel queries do not wait on each other.
+fn invoke_unlocked(key: &str, id: &str) -> Option<CveRecord> {
+    let provider = {
+        let cve_apis = CVE_API.lock().unwrap();
+        if let Some(cve) = cve_apis.cache_get(key, id) {
+            return Some(cve);
+        }
+        if cve_apis.is_offline() && cve_apis.is_remote(key) {
+            return None;
+        }
+        cve_apis.provider(key)?
+    };
+    let cve = provider.query(id);
+    CVE_API.lock().unwrap().cache_put(key, id, &cve);
+    Some(cve)
+}
+
 fn add_worksheet<'a>(out: &'a mut Workbook, name: String, format: &'a Format) -> Worksheet<'a> {
     let mut sheet = out.add_worksheet(Some(&name)).unwrap();
     sheet.write_string(0, 0, "cve", Some(format)).unwrap();
@@ -219,5 +303,13 @@ fn add_worksheet<'a>(out: &'a mut Workbook, name: String, format: &'a Format) ->
         .unwrap();
     sheet.write_string(0, 7, "score", Some(format)).unwrap();
     sheet.write_string(0, 8, "effect", Some(format)).unwrap();
+    sheet.write_string(0, 9, "source", Some(format)).unwrap();
+    sheet.write_string(0, 10, "vector", Some(format)).unwrap();
+    sheet.write_string(0, 11, "epss", Some(format)).unwrap();
+    sheet.write_string(0, 12, "percentile", Some(format)).unwrap();
+    sheet.write_string(0, 13, "kev_date_added", Some(format)).unwrap();
+    sheet.write_string(0, 14, "kev_due_date", Some(format)).unwrap();
+    sheet.write_string(0, 15, "kev_required_action", Some(format)).unwrap();
+    sheet.autofilter(0, 0, 60000, 15).unwrap();
     sheet
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::{fs, path::Path};
 
-use crate::command::lib::image;
+use crate::command::lib::{image, text};
+use encoding_rs::Encoding;
 use clap::{value_parser, App, Arg, ArgAction, ArgMatches, Command};
 
 pub fn new_sub_command<'help>() -> App<'help> {
@@ -26,6 +27,7 @@ pub fn new_sub_command<'help>() -> App<'help> {
                 .short('o')
                 .help("输出的镜像信息文件名称"),
         )
+        .arg(text::encoding_arg())
         .override_usage("etool cve -f ./image.txt -p ./tmp -o image.json\n  ")
 }
 
@@ -36,9 +38,10 @@ pub fn handler(matches: &ArgMatches) {
         .collect::<Vec<&String>>();
     let path = matches.get_one::<String>("path").unwrap();
     let output = matches.get_one::<String>("output").unwrap();
+    let encoding = matches.get_one::<&'static Encoding>("encoding").copied();
     if !Path::exists(Path::new(path)) {
         fs::create_dir(path).unwrap();
     };
 
-    image::dump(files, path, output);
+    image::dump(files, path, output, encoding);
 }
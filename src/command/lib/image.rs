@@ -1,3 +1,5 @@
+use crate::command::lib::text;
+use encoding_rs::Encoding;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
@@ -74,7 +76,7 @@ pub fn run_inspect_cmd(cmd: &mut InspectCmd) {
     cmd.handler(&out);
 }
 
-fn multi_run_inspect_cmd(files: Vec<&str>) -> String {
+fn multi_run_inspect_cmd(files: Vec<&str>, encoding: Option<&'static Encoding>) -> String {
     let mut image_layer: HashMap<String, InspectCmd> = HashMap::new();
     let target: Vec<&str> = if files.is_empty() {
         vec!["image.txt"]
@@ -85,7 +87,7 @@ fn multi_run_inspect_cmd(files: Vec<&str>) -> String {
     for file in target {
         let f = file.trim();
         if !f.is_empty() {
-            let data = std::fs::read_to_string(file).unwrap();
+            let data = text::read(file, encoding).unwrap();
             let images: Vec<String> = data
                 .trim()
                 .split('\n')
@@ -110,8 +112,8 @@ fn multi_run_inspect_cmd(files: Vec<&str>) -> String {
     }
 }
 
-pub fn dump(input: Vec<&String>, path: &str, output: &str) {
-    let image_layers = multi_run_inspect_cmd(input.iter().map(|x| x.as_str()).collect());
+pub fn dump(input: Vec<&String>, path: &str, output: &str, encoding: Option<&'static Encoding>) {
+    let image_layers = multi_run_inspect_cmd(input.iter().map(|x| x.as_str()).collect(), encoding);
     if !image_layers.is_empty() {
         fs::write(Path::new(path).join(output), image_layers).unwrap();
     }
@@ -211,7 +213,7 @@ mod test {
 
     #[test]
     fn test_multi_run_inspect_cmd() {
-        multi_run_inspect_cmd(vec![]);
+        multi_run_inspect_cmd(vec![], None);
     }
 
     #[test]
@@ -1 +1,3 @@
-pub mod image;
\ No newline at end of file
+pub mod image;
+pub mod table;
+pub mod text;
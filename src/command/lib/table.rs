@@ -0,0 +1,179 @@
+use crate::command::lib::text;
+use calamine::{open_workbook_auto, DataType, Range, Reader};
+use clap::Arg;
+use encoding_rs::Encoding;
+use std::error::Error;
+use std::path::Path;
+
+/// A sheet name and its cells, a delimited text file being one sheet named after the file.
+pub type Sheet = (String, Range<DataType>);
+
+/// The extensions calamine reads, other files are sniffed.
+const SPREADSHEET_EXTENSIONS: [&str; 7] = ["xlsx", "xlsm", "xlam", "xls", "xla", "xlsb", "ods"];
+/// The delimiters `sniff_delimiter` tells apart.
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// The `--delimiter` option of the commands reading tables.
+pub fn delimiter_arg<'help>() -> Arg<'help> {
+    Arg::new("delimiter")
+        .takes_value(true)
+        .long("delimiter")
+        .value_parser(parse_delimiter)
+        .help("CSV/TSV文件的分隔符(如 , ; | tab), 默认按扩展名和内容识别")
+}
+
+/// A single ASCII character, or `tab`/`\t` for a tab.
+pub fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!("invalid delimiter {}", s)),
+    }
+}
+
+/// Every sheet of a spreadsheet calamine reads (xlsx, xls, xlsb, ods) or the one sheet of a
+/// CSV/TSV file decoded by `text::read`. The extension decides, a file with another extension is
+/// opened as a spreadsheet when its content is one and as delimited text otherwise.
+pub fn open(
+    path: &str,
+    delimiter: Option<u8>,
+    encoding: Option<&'static Encoding>,
+) -> Result<Vec<Sheet>, Box<dyn Error>> {
+    let extension = Path::new(path)
+        .extension()
+        .map(|x| x.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let is_text = ["csv", "tsv", "tab", "txt"].contains(&extension.as_str());
+    if !is_text {
+        match open_workbook_auto(path) {
+            Ok(mut workbook) => return Ok(workbook.worksheets()),
+            Err(e) if SPREADSHEET_EXTENSIONS.contains(&extension.as_str()) => return Err(e.into()),
+            Err(_) => {}
+        }
+    }
+    let delimiter = match (delimiter, extension.as_str()) {
+        (Some(v), _) => Some(v),
+        (None, "tsv") | (None, "tab") => Some(b'\t'),
+        _ => None,
+    };
+    let name = Path::new(path)
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(vec![(name, parser_delimited(&text::read(path, encoding)?, delimiter)?)])
+}
+
+/// The sheet called `name`, or the only sheet of a delimited file whatever its name.
+pub fn open_sheet(
+    path: &str,
+    name: &str,
+    delimiter: Option<u8>,
+    encoding: Option<&'static Encoding>,
+) -> Result<Range<DataType>, Box<dyn Error>> {
+    let mut sheets = open(path, delimiter, encoding)?;
+    if sheets.len() == 1 && !is_spreadsheet(path) {
+        return Ok(sheets.remove(0).1);
+    }
+    match sheets.into_iter().find(|x| x.0 == name) {
+        Some(sheet) => Ok(sheet.1),
+        None => Err(format!("no sheet {} in {}", name, path).into()),
+    }
+}
+
+fn is_spreadsheet(path: &str) -> bool {
+    let path = path.to_lowercase();
+    SPREADSHEET_EXTENSIONS.iter().any(|x| path.ends_with(&format!(".{}", x)))
+}
+
+/// Delimited text as a sheet of string cells, sniffing the delimiter when none is given.
+pub fn parser_delimited(data: &str, delimiter: Option<u8>) -> Result<Range<DataType>, csv::Error> {
+    let data = data.trim_start_matches('\u{feff}');
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter.unwrap_or_else(|| sniff_delimiter(data)))
+        .from_reader(data.as_bytes());
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for record in reader.records() {
+        rows.push(record?.iter().map(|x| x.to_string()).collect());
+    }
+    Ok(from_rows(rows))
+}
+
+/// The delimiter occurring most often outside quotes in the first line, `,` when none does.
+pub fn sniff_delimiter(data: &str) -> u8 {
+    let line = data.lines().find(|x| !x.trim().is_empty()).unwrap_or_default();
+    let mut counts = [0usize; DELIMITERS.len()];
+    let mut quoted = false;
+    for b in line.bytes() {
+        if b == b'"' {
+            quoted = !quoted;
+        } else if let Some(i) = DELIMITERS.iter().position(|x| *x == b && !quoted) {
+            counts[i] += 1;
+        }
+    }
+    match (0..DELIMITERS.len()).rev().max_by_key(|&i| counts[i]) {
+        Some(i) if counts[i] > 0 => DELIMITERS[i],
+        _ => b',',
+    }
+}
+
+/// A sheet of string cells, empty fields and the missing cells of short rows left empty.
+pub fn from_rows(rows: Vec<Vec<String>>) -> Range<DataType> {
+    let width = rows.iter().map(|x| x.len()).max().unwrap_or_default();
+    if rows.is_empty() || width == 0 {
+        return Range::empty();
+    }
+    let mut range = Range::new((0, 0), (rows.len() as u32 - 1, width as u32 - 1));
+    for (i, row) in rows.into_iter().enumerate() {
+        for (j, cell) in row.into_iter().enumerate() {
+            if !cell.is_empty() {
+                range.set_value((i as u32, j as u32), DataType::String(cell));
+            }
+        }
+    }
+    range
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parser_delimited() {
+        assert_eq!(sniff_delimiter("Component;Version;\"a,b,c\"\n"), b';');
+        assert_eq!(sniff_delimiter("Component\tVersion\n"), b'\t');
+        assert_eq!(sniff_delimiter("Component\n"), b',');
+        assert_eq!(parse_delimiter("tab"), Ok(b'\t'));
+        assert!(parse_delimiter(",,").is_err());
+
+        let range = parser_delimited("\u{feff}Component,Version,CVE\nopenssl,1.0.2k,CVE-2016-2105\n\"a, b\",1.0\nzlib,,\n", None).unwrap();
+        assert_eq!(range.get_size(), (4, 3));
+        assert_eq!(range.get_value((0, 0)), Some(&DataType::String(String::from("Component"))));
+        assert_eq!(range.get_value((2, 0)), Some(&DataType::String(String::from("a, b"))));
+        assert_eq!(range.get_value((2, 2)), Some(&DataType::Empty));
+        assert_eq!(range.get_value((3, 1)), Some(&DataType::Empty));
+    }
+
+    #[test]
+    fn test_open() {
+        let dir = std::env::temp_dir().join("etool-table-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("student.tsv");
+        fs::write(&path, "name\tclass\nalice\t1\n").unwrap();
+        let sheets = open(path.to_str().unwrap(), None, None).unwrap();
+        assert_eq!(sheets.len(), 1);
+        assert_eq!(sheets[0].0, "student");
+        assert_eq!(sheets[0].1.get_value((1, 1)), Some(&DataType::String(String::from("1"))));
+
+        // an unknown extension that is not a spreadsheet is read as text
+        let path = dir.join("student.export");
+        fs::write(&path, "name|class\nalice|1\n").unwrap();
+        let range = open_sheet(path.to_str().unwrap(), "Sheet1", None, None).unwrap();
+        assert_eq!(range.get_size(), (2, 2));
+        assert!(open(dir.join("missing.xlsx").to_str().unwrap(), None, None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+use chardetng::EncodingDetector;
+use clap::Arg;
+use encoding_rs::{Encoding, UTF_8};
+use std::error::Error;
+use std::fs;
+
+/// The `--encoding` option of the commands reading text files.
+pub fn encoding_arg<'help>() -> Arg<'help> {
+    Arg::new("encoding")
+        .takes_value(true)
+        .long("encoding")
+        .value_parser(parse_encoding)
+        .help("输入文本文件的编码(如 utf-8 gbk gb18030 utf-16le), 默认按BOM和内容识别")
+}
+
+/// An encoding label like `gbk` or `utf-16le`, as the WHATWG encoding standard names them.
+pub fn parse_encoding(s: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(s.trim().as_bytes()).ok_or(format!("unknown encoding {}", s))
+}
+
+/// Reads a text file as `decode` does.
+pub fn read(path: &str, encoding: Option<&'static Encoding>) -> Result<String, Box<dyn Error>> {
+    Ok(decode(&fs::read(path)?, encoding))
+}
+
+/// Decodes text without its BOM. A UTF-8 or UTF-16 BOM decides the encoding, else `encoding`,
+/// else valid UTF-8 stays UTF-8 and anything else is guessed from the content, e.g. GBK.
+pub fn decode(bytes: &[u8], encoding: Option<&'static Encoding>) -> String {
+    let encoding = match (Encoding::for_bom(bytes), encoding) {
+        (Some((bom, _)), _) => bom,
+        (None, Some(v)) => v,
+        (None, None) => detect(bytes),
+    };
+    encoding.decode(bytes).0.into_owned()
+}
+
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use encoding_rs::{GBK, UTF_16LE};
+
+    const ROSTER_STR: &str = "姓名,班级,学号,目录\n张三,一年级二班,20230101,张三\n李四,一年级三班,20230102,李四\n";
+
+    #[test]
+    fn test_decode() {
+        let (gbk, _, _) = GBK.encode(ROSTER_STR);
+        assert_eq!(decode(&gbk, None), ROSTER_STR);
+        assert_eq!(decode(&gbk, Some(parse_encoding("gb18030").unwrap())), ROSTER_STR);
+
+        let mut utf8 = vec![0xef, 0xbb, 0xbf];
+        utf8.extend_from_slice(ROSTER_STR.as_bytes());
+        assert_eq!(decode(&utf8, None), ROSTER_STR);
+        // the BOM wins over a wrong override
+        assert_eq!(decode(&utf8, Some(GBK)), ROSTER_STR);
+
+        let mut utf16 = vec![0xff, 0xfe];
+        for unit in ROSTER_STR.encode_utf16() {
+            utf16.extend_from_slice(&unit.to_le_bytes());
+        }
+        assert_eq!(decode(&utf16, None), ROSTER_STR);
+        assert_eq!(parse_encoding("UTF-16LE"), Ok(UTF_16LE));
+        assert!(parse_encoding("klingon").is_err());
+    }
+}
@@ -1,7 +1,9 @@
 use std::{fs, path::Path};
 
 use clap::{App, Arg, ArgMatches, Command};
-use calamine::{open_workbook, DataType, Xlsx, Reader};
+use calamine::DataType;
+use crate::command::lib::{table, text};
+use encoding_rs::Encoding;
 
 pub fn new_sub_command<'help>() -> App<'help> {
     Command::new("student")
@@ -16,7 +18,7 @@ pub fn new_sub_command<'help>() -> App<'help> {
             Arg::new("file")
                 .default_value("./student.xlsx")
                 .short('f')
-                .help("待处理的Excel文件路径"),
+                .help("待处理的表格文件路径(xlsx/xls/xlsb/ods/csv/tsv)"),
         )
         .arg(
             Arg::new("sheet")
@@ -36,6 +38,8 @@ pub fn new_sub_command<'help>() -> App<'help> {
                 .long("row")
                 .help("待处理的表格行序号(从0开始)"),
         )
+        .arg(table::delimiter_arg())
+        .arg(text::encoding_arg())
         .override_usage("etool student -p ./tmp -f student.xlsx --sheet Sheet1 --row 2 --col 3\n  ")
 }
 
@@ -46,6 +50,8 @@ pub fn handler(matches: &ArgMatches) {
     let sheet = matches.get_one::<String>("sheet").unwrap();
     let row = matches.get_one::<String>("row").unwrap();
     let column = matches.get_one::<String>("column").unwrap();
+    let delimiter = matches.get_one::<u8>("delimiter").copied();
+    let encoding = matches.get_one::<&'static Encoding>("encoding").copied();
     println!("{}行-{}列", row, column);
     let row = row.parse::<usize>().unwrap();
     let column = column.parse::<usize>().unwrap();
@@ -53,8 +59,7 @@ pub fn handler(matches: &ArgMatches) {
     if !Path::exists(Path::new(path)) {
         fs::create_dir(path).unwrap();
     };
-    let mut workbook: Xlsx<_> = open_workbook(file).unwrap();
-    let range = workbook.worksheet_range(sheet).unwrap();
+    let range = table::open_sheet(file, sheet, delimiter, encoding).unwrap();
     for (index, vals) in range.rows().enumerate() {
         if index < row {
             continue;
@@ -32,6 +32,9 @@ fn main() {
                 Some(("export", matches)) => {
                     command::cve::exporter::handler(matches);
                 }
+                Some(("search", matches)) => {
+                    command::cve::search::handler(matches);
+                }
                 _ => cve_command.print_help().unwrap_or_else(|err| {
                     println!("{:#?}", err);
                 })